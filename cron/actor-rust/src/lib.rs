@@ -8,8 +8,18 @@ struct CronActor {}
 
 #[async_trait]
 impl Cron for CronActor {
-    async fn timed_invoke(&self, _ctx: &Context, req: &u64) -> RpcResult<()> {
-        info!("Timed invoke at {req}");
+    async fn timed_invoke(&self, _ctx: &Context, req: &CronEvent) -> RpcResult<()> {
+        if req.catch_up {
+            info!(
+                "Catching up on timed invoke #{} of '{}' scheduled at {}",
+                req.sequence, req.expression, req.scheduled_timestamp
+            );
+        } else {
+            info!(
+                "Timed invoke #{} of '{}' at {}",
+                req.sequence, req.expression, req.actual_timestamp
+            );
+        }
         Ok(())
     }
 }
@@ -1,6 +1,8 @@
 package main
 
 import (
+	"fmt"
+
 	"github.com/wasmcloud/actor-tinygo"
 
 	"github.com/wasmcloud/examples/cron/interface/tinygo"
@@ -18,7 +20,8 @@ type CronActor struct {
 	Logger *logging.LoggingSender
 }
 
-func (e *CronActor) TimedInvoke(ctx *actor.Context, req uint64) error {
-	_ = e.Logger.WriteLog(ctx, logging.LogEntry{Level: "info", Text: "Timed Invoke!!"})
+func (e *CronActor) TimedInvoke(ctx *actor.Context, req cron.CronEvent) error {
+	text := fmt.Sprintf("Timed Invoke #%d of '%s'!!", req.Sequence, req.Expression)
+	_ = e.Logger.WriteLog(ctx, logging.LogEntry{Level: "info", Text: text})
 	return nil
 }
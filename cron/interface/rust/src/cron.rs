@@ -22,6 +22,555 @@ use wasmbus_rpc::{
 #[allow(dead_code)]
 pub const SMITHY_VERSION: &str = "1.0";
 
+/// Describes the scheduled occurrence an actor is being invoked for
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CronEvent {
+    /// Time the actor was actually invoked, in milliseconds since the epoch (UTC).
+    /// This is later than scheduledTimestamp for catch-up invocations.
+    #[serde(rename = "actualTimestamp")]
+    #[serde(default)]
+    pub actual_timestamp: u64,
+    /// True if this invocation is catching up on an occurrence that was
+    /// missed while the provider was down or a previous invocation was slow
+    #[serde(rename = "catchUp")]
+    #[serde(default)]
+    pub catch_up: bool,
+    /// The cron expression of the schedule that fired, or its `at` or `every` timer
+    #[serde(default)]
+    pub expression: String,
+    /// Name of the link whose schedule fired
+    #[serde(rename = "linkName")]
+    #[serde(default)]
+    pub link_name: String,
+    /// Name of the schedule that fired. A link with a single unnamed schedule
+    /// uses the name "default"
+    #[serde(rename = "scheduleName")]
+    #[serde(default)]
+    pub schedule_name: String,
+    /// Time the occurrence was scheduled for, in milliseconds since the epoch (UTC)
+    #[serde(rename = "scheduledTimestamp")]
+    #[serde(default)]
+    pub scheduled_timestamp: u64,
+    /// Number of this invocation of the schedule, starting at 1
+    #[serde(default)]
+    pub sequence: u64,
+}
+
+// Encode CronEvent as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_cron_event<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &CronEvent,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.map(7)?;
+    e.str("actualTimestamp")?;
+    e.u64(val.actual_timestamp)?;
+    e.str("catchUp")?;
+    e.bool(val.catch_up)?;
+    e.str("expression")?;
+    e.str(&val.expression)?;
+    e.str("linkName")?;
+    e.str(&val.link_name)?;
+    e.str("scheduleName")?;
+    e.str(&val.schedule_name)?;
+    e.str("scheduledTimestamp")?;
+    e.u64(val.scheduled_timestamp)?;
+    e.str("sequence")?;
+    e.u64(val.sequence)?;
+    Ok(())
+}
+
+// Decode CronEvent from cbor input stream
+#[doc(hidden)]
+pub fn decode_cron_event(d: &mut wasmbus_rpc::cbor::Decoder<'_>) -> Result<CronEvent, RpcError> {
+    let __result = {
+        let mut actual_timestamp: Option<u64> = None;
+        let mut catch_up: Option<bool> = None;
+        let mut expression: Option<String> = None;
+        let mut link_name: Option<String> = None;
+        let mut schedule_name: Option<String> = None;
+        let mut scheduled_timestamp: Option<u64> = None;
+        let mut sequence: Option<u64> = None;
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct CronEvent, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => actual_timestamp = Some(d.u64()?),
+                    1 => catch_up = Some(d.bool()?),
+                    2 => expression = Some(d.str()?.to_string()),
+                    3 => link_name = Some(d.str()?.to_string()),
+                    4 => schedule_name = Some(d.str()?.to_string()),
+                    5 => scheduled_timestamp = Some(d.u64()?),
+                    6 => sequence = Some(d.u64()?),
+                    _ => d.skip()?,
+                }
+            }
+        } else {
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "actualTimestamp" => actual_timestamp = Some(d.u64()?),
+                    "catchUp" => catch_up = Some(d.bool()?),
+                    "expression" => expression = Some(d.str()?.to_string()),
+                    "linkName" => link_name = Some(d.str()?.to_string()),
+                    "scheduleName" => schedule_name = Some(d.str()?.to_string()),
+                    "scheduledTimestamp" => scheduled_timestamp = Some(d.u64()?),
+                    "sequence" => sequence = Some(d.u64()?),
+                    _ => d.skip()?,
+                }
+            }
+        }
+        CronEvent {
+            actual_timestamp: if let Some(__x) = actual_timestamp {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field CronEvent.actual_timestamp (#0)".to_string(),
+                ));
+            },
+
+            catch_up: if let Some(__x) = catch_up {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field CronEvent.catch_up (#1)".to_string(),
+                ));
+            },
+
+            expression: if let Some(__x) = expression {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field CronEvent.expression (#2)".to_string(),
+                ));
+            },
+
+            link_name: if let Some(__x) = link_name {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field CronEvent.link_name (#3)".to_string(),
+                ));
+            },
+
+            schedule_name: if let Some(__x) = schedule_name {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field CronEvent.schedule_name (#4)".to_string(),
+                ));
+            },
+
+            scheduled_timestamp: if let Some(__x) = scheduled_timestamp {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field CronEvent.scheduled_timestamp (#5)".to_string(),
+                ));
+            },
+
+            sequence: if let Some(__x) = sequence {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field CronEvent.sequence (#6)".to_string(),
+                ));
+            },
+        }
+    };
+    Ok(__result)
+}
+/// Describes a schedule created by an actor
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ScheduleInfo {
+    /// The cron expression of the schedule, or its at or every timer
+    #[serde(default)]
+    pub expression: String,
+    /// Name of the schedule
+    #[serde(default)]
+    pub name: String,
+    /// Time of the next occurrence, in milliseconds since the epoch (UTC).
+    /// Not set if the schedule has no more occurrences
+    #[serde(rename = "nextTimestamp")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_timestamp: Option<u64>,
+}
+
+// Encode ScheduleInfo as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_schedule_info<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &ScheduleInfo,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.map(3)?;
+    e.str("expression")?;
+    e.str(&val.expression)?;
+    e.str("name")?;
+    e.str(&val.name)?;
+    if let Some(val) = val.next_timestamp.as_ref() {
+        e.str("nextTimestamp")?;
+        e.u64(*val)?;
+    } else {
+        e.null()?;
+    }
+    Ok(())
+}
+
+// Decode ScheduleInfo from cbor input stream
+#[doc(hidden)]
+pub fn decode_schedule_info(
+    d: &mut wasmbus_rpc::cbor::Decoder<'_>,
+) -> Result<ScheduleInfo, RpcError> {
+    let __result = {
+        let mut expression: Option<String> = None;
+        let mut name: Option<String> = None;
+        let mut next_timestamp: Option<Option<u64>> = Some(None);
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct ScheduleInfo, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => expression = Some(d.str()?.to_string()),
+                    1 => name = Some(d.str()?.to_string()),
+                    2 => {
+                        next_timestamp = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u64()?))
+                        }
+                    }
+                    _ => d.skip()?,
+                }
+            }
+        } else {
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "expression" => expression = Some(d.str()?.to_string()),
+                    "name" => name = Some(d.str()?.to_string()),
+                    "nextTimestamp" => {
+                        next_timestamp = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u64()?))
+                        }
+                    }
+                    _ => d.skip()?,
+                }
+            }
+        }
+        ScheduleInfo {
+            expression: if let Some(__x) = expression {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field ScheduleInfo.expression (#0)".to_string(),
+                ));
+            },
+
+            name: if let Some(__x) = name {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field ScheduleInfo.name (#1)".to_string(),
+                ));
+            },
+
+            next_timestamp: next_timestamp.unwrap(),
+        }
+    };
+    Ok(__result)
+}
+/// A list of schedules
+pub type ScheduleInfos = Vec<ScheduleInfo>;
+
+// Encode ScheduleInfos as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_schedule_infos<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &ScheduleInfos,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.array(val.len() as u64)?;
+    for item in val.iter() {
+        encode_schedule_info(e, item)?;
+    }
+    Ok(())
+}
+
+// Decode ScheduleInfos from cbor input stream
+#[doc(hidden)]
+pub fn decode_schedule_infos(
+    d: &mut wasmbus_rpc::cbor::Decoder<'_>,
+) -> Result<ScheduleInfos, RpcError> {
+    let __result = {
+        if let Some(n) = d.array()? {
+            let mut arr: Vec<ScheduleInfo> = Vec::with_capacity(n as usize);
+            for _ in 0..(n as usize) {
+                arr.push(decode_schedule_info(d).map_err(|e| {
+                    format!("decoding 'org.wasmcloud.example.cron#ScheduleInfo': {}", e)
+                })?)
+            }
+            arr
+        } else {
+            // indefinite array
+            let mut arr: Vec<ScheduleInfo> = Vec::new();
+            loop {
+                match d.datatype() {
+                    Err(_) => break,
+                    Ok(wasmbus_rpc::cbor::Type::Break) => break,
+                    Ok(_) => arr.push(decode_schedule_info(d).map_err(|e| {
+                        format!("decoding 'org.wasmcloud.example.cron#ScheduleInfo': {}", e)
+                    })?),
+                }
+            }
+            arr
+        }
+    };
+    Ok(__result)
+}
+/// A schedule created by an actor. Exactly one of expression, at and
+/// every must be set; they are written the same way as the link values
+/// of the same names.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ScheduleRequest {
+    /// An RFC 3339 time to fire once at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at: Option<String>,
+    /// A duration to fire at intervals of, like "90s"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every: Option<String>,
+    /// A full cron expression
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression: Option<String>,
+    /// Name of the schedule, delivered as scheduleName in each CronEvent.
+    /// It must not be the name of one of the link's schedules.
+    #[serde(default)]
+    pub name: String,
+    /// An RFC 3339 time for the first occurrence of an every timer
+    #[serde(rename = "startAt")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_at: Option<String>,
+    /// IANA time zone the cron expression is evaluated in. Defaults to UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+// Encode ScheduleRequest as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_schedule_request<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &ScheduleRequest,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.map(6)?;
+    if let Some(val) = val.at.as_ref() {
+        e.str("at")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    if let Some(val) = val.every.as_ref() {
+        e.str("every")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    if let Some(val) = val.expression.as_ref() {
+        e.str("expression")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    e.str("name")?;
+    e.str(&val.name)?;
+    if let Some(val) = val.start_at.as_ref() {
+        e.str("startAt")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    if let Some(val) = val.timezone.as_ref() {
+        e.str("timezone")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    Ok(())
+}
+
+// Decode ScheduleRequest from cbor input stream
+#[doc(hidden)]
+pub fn decode_schedule_request(
+    d: &mut wasmbus_rpc::cbor::Decoder<'_>,
+) -> Result<ScheduleRequest, RpcError> {
+    let __result = {
+        let mut at: Option<Option<String>> = Some(None);
+        let mut every: Option<Option<String>> = Some(None);
+        let mut expression: Option<Option<String>> = Some(None);
+        let mut name: Option<String> = None;
+        let mut start_at: Option<Option<String>> = Some(None);
+        let mut timezone: Option<Option<String>> = Some(None);
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct ScheduleRequest, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => {
+                        at = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    1 => {
+                        every = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    2 => {
+                        expression = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    3 => name = Some(d.str()?.to_string()),
+                    4 => {
+                        start_at = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    5 => {
+                        timezone = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    _ => d.skip()?,
+                }
+            }
+        } else {
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "at" => {
+                        at = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "every" => {
+                        every = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "expression" => {
+                        expression = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "name" => name = Some(d.str()?.to_string()),
+                    "startAt" => {
+                        start_at = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "timezone" => {
+                        timezone = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    _ => d.skip()?,
+                }
+            }
+        }
+        ScheduleRequest {
+            at: at.unwrap(),
+            every: every.unwrap(),
+            expression: expression.unwrap(),
+            name: if let Some(__x) = name {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field ScheduleRequest.name (#3)".to_string(),
+                ));
+            },
+
+            start_at: start_at.unwrap(),
+            timezone: timezone.unwrap(),
+        }
+    };
+    Ok(__result)
+}
 /// The Cron service has a single method, timed_invoke, which
 /// invokes an actor after a specified interval
 /// wasmbus.contractId: wasmcloud:example:cron
@@ -33,7 +582,7 @@ pub trait Cron {
         "wasmcloud:example:cron"
     }
     /// Invoked on an actor on the interval specified in the link
-    async fn timed_invoke(&self, ctx: &Context, arg: &u64) -> RpcResult<()>;
+    async fn timed_invoke(&self, ctx: &Context, arg: &CronEvent) -> RpcResult<()>;
 }
 
 /// CronReceiver receives messages defined in the Cron service trait
@@ -45,8 +594,8 @@ pub trait CronReceiver: MessageDispatch + Cron {
     async fn dispatch(&self, ctx: &Context, message: Message<'_>) -> Result<Vec<u8>, RpcError> {
         match message.method {
             "TimedInvoke" => {
-                let value: u64 = wasmbus_rpc::common::deserialize(&message.arg)
-                    .map_err(|e| RpcError::Deser(format!("'U64': {}", e)))?;
+                let value: CronEvent = wasmbus_rpc::common::deserialize(&message.arg)
+                    .map_err(|e| RpcError::Deser(format!("'CronEvent': {}", e)))?;
 
                 let _resp = Cron::timed_invoke(self, ctx, &value).await?;
                 let buf = Vec::new();
@@ -104,7 +653,7 @@ impl CronSender<wasmbus_rpc::actor::prelude::WasmHost> {
 impl<T: Transport + std::marker::Sync + std::marker::Send> Cron for CronSender<T> {
     #[allow(unused)]
     /// Invoked on an actor on the interval specified in the link
-    async fn timed_invoke(&self, ctx: &Context, arg: &u64) -> RpcResult<()> {
+    async fn timed_invoke(&self, ctx: &Context, arg: &CronEvent) -> RpcResult<()> {
         let buf = wasmbus_rpc::common::serialize(arg)?;
 
         let resp = self
@@ -121,3 +670,184 @@ impl<T: Transport + std::marker::Sync + std::marker::Send> Cron for CronSender<T
         Ok(())
     }
 }
+/// The CronScheduler service lets an actor create and remove its own
+/// schedules at runtime, in addition to those in its link. Each occurrence
+/// of a schedule is delivered to the actor with TimedInvoke.
+/// wasmbus.contractId: wasmcloud:example:cron
+/// wasmbus.providerReceive
+#[async_trait]
+pub trait CronScheduler {
+    /// returns the capability contract id for this interface
+    fn contract_id() -> &'static str {
+        "wasmcloud:example:cron"
+    }
+    /// Creates a schedule for the calling actor, replacing any schedule of
+    /// the actor with the same name
+    async fn schedule(&self, ctx: &Context, arg: &ScheduleRequest) -> RpcResult<ScheduleInfo>;
+    /// Removes one of the calling actor's schedules, by name.
+    /// Returns true if the schedule existed
+    async fn cancel<TS: ToString + ?Sized + std::marker::Sync>(
+        &self,
+        ctx: &Context,
+        arg: &TS,
+    ) -> RpcResult<bool>;
+    /// Returns the schedules created by the calling actor, sorted by name
+    async fn list_schedules(&self, ctx: &Context) -> RpcResult<ScheduleInfos>;
+}
+
+/// CronSchedulerReceiver receives messages defined in the CronScheduler service trait
+/// The CronScheduler service lets an actor create and remove its own
+/// schedules at runtime, in addition to those in its link. Each occurrence
+/// of a schedule is delivered to the actor with TimedInvoke.
+#[doc(hidden)]
+#[async_trait]
+pub trait CronSchedulerReceiver: MessageDispatch + CronScheduler {
+    async fn dispatch(&self, ctx: &Context, message: Message<'_>) -> Result<Vec<u8>, RpcError> {
+        match message.method {
+            "Schedule" => {
+                let value: ScheduleRequest = wasmbus_rpc::common::deserialize(&message.arg)
+                    .map_err(|e| RpcError::Deser(format!("'ScheduleRequest': {}", e)))?;
+
+                let resp = CronScheduler::schedule(self, ctx, &value).await?;
+                let buf = wasmbus_rpc::common::serialize(&resp)?;
+
+                Ok(buf)
+            }
+            "Cancel" => {
+                let value: String = wasmbus_rpc::common::deserialize(&message.arg)
+                    .map_err(|e| RpcError::Deser(format!("'String': {}", e)))?;
+
+                let resp = CronScheduler::cancel(self, ctx, &value).await?;
+                let buf = wasmbus_rpc::common::serialize(&resp)?;
+
+                Ok(buf)
+            }
+            "ListSchedules" => {
+                let resp = CronScheduler::list_schedules(self, ctx).await?;
+                let buf = wasmbus_rpc::common::serialize(&resp)?;
+
+                Ok(buf)
+            }
+            _ => Err(RpcError::MethodNotHandled(format!(
+                "CronScheduler::{}",
+                message.method
+            ))),
+        }
+    }
+}
+
+/// CronSchedulerSender sends messages to a CronScheduler service
+/// The CronScheduler service lets an actor create and remove its own
+/// schedules at runtime, in addition to those in its link. Each occurrence
+/// of a schedule is delivered to the actor with TimedInvoke.
+/// client for sending CronScheduler messages
+#[derive(Clone, Debug)]
+pub struct CronSchedulerSender<T: Transport> {
+    transport: T,
+}
+
+impl<T: Transport> CronSchedulerSender<T> {
+    /// Constructs a CronSchedulerSender with the specified transport
+    pub fn via(transport: T) -> Self {
+        Self { transport }
+    }
+
+    pub fn set_timeout(&self, interval: std::time::Duration) {
+        self.transport.set_timeout(interval);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl CronSchedulerSender<wasmbus_rpc::actor::prelude::WasmHost> {
+    /// Constructs a client for sending to a CronScheduler provider
+    /// implementing the 'wasmcloud:example:cron' capability contract, with the "default" link
+    pub fn new() -> Self {
+        let transport =
+            wasmbus_rpc::actor::prelude::WasmHost::to_provider("wasmcloud:example:cron", "default")
+                .unwrap();
+        Self { transport }
+    }
+
+    /// Constructs a client for sending to a CronScheduler provider
+    /// implementing the 'wasmcloud:example:cron' capability contract, with the specified link name
+    pub fn new_with_link(link_name: &str) -> wasmbus_rpc::error::RpcResult<Self> {
+        let transport = wasmbus_rpc::actor::prelude::WasmHost::to_provider(
+            "wasmcloud:example:cron",
+            link_name,
+        )?;
+        Ok(Self { transport })
+    }
+}
+#[async_trait]
+impl<T: Transport + std::marker::Sync + std::marker::Send> CronScheduler
+    for CronSchedulerSender<T>
+{
+    #[allow(unused)]
+    /// Creates a schedule for the calling actor, replacing any schedule of
+    /// the actor with the same name
+    async fn schedule(&self, ctx: &Context, arg: &ScheduleRequest) -> RpcResult<ScheduleInfo> {
+        let buf = wasmbus_rpc::common::serialize(arg)?;
+
+        let resp = self
+            .transport
+            .send(
+                ctx,
+                Message {
+                    method: "CronScheduler.Schedule",
+                    arg: Cow::Borrowed(&buf),
+                },
+                None,
+            )
+            .await?;
+
+        let value: ScheduleInfo = wasmbus_rpc::common::deserialize(&resp)
+            .map_err(|e| RpcError::Deser(format!("'{}': ScheduleInfo", e)))?;
+        Ok(value)
+    }
+    #[allow(unused)]
+    /// Removes one of the calling actor's schedules, by name.
+    /// Returns true if the schedule existed
+    async fn cancel<TS: ToString + ?Sized + std::marker::Sync>(
+        &self,
+        ctx: &Context,
+        arg: &TS,
+    ) -> RpcResult<bool> {
+        let buf = wasmbus_rpc::common::serialize(&arg.to_string())?;
+
+        let resp = self
+            .transport
+            .send(
+                ctx,
+                Message {
+                    method: "CronScheduler.Cancel",
+                    arg: Cow::Borrowed(&buf),
+                },
+                None,
+            )
+            .await?;
+
+        let value: bool = wasmbus_rpc::common::deserialize(&resp)
+            .map_err(|e| RpcError::Deser(format!("'{}': Boolean", e)))?;
+        Ok(value)
+    }
+    #[allow(unused)]
+    /// Returns the schedules created by the calling actor, sorted by name
+    async fn list_schedules(&self, ctx: &Context) -> RpcResult<ScheduleInfos> {
+        let buf = *b"";
+        let resp = self
+            .transport
+            .send(
+                ctx,
+                Message {
+                    method: "CronScheduler.ListSchedules",
+                    arg: Cow::Borrowed(&buf),
+                },
+                None,
+            )
+            .await?;
+
+        let value: ScheduleInfos = wasmbus_rpc::common::deserialize(&resp)
+            .map_err(|e| RpcError::Deser(format!("'{}': ScheduleInfos", e)))?;
+        Ok(value)
+    }
+}
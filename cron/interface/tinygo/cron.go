@@ -6,11 +6,261 @@ import (
 	//nolint
 )
 
+// Describes the scheduled occurrence an actor is being invoked for
+type CronEvent struct {
+	// Time the actor was actually invoked, in milliseconds since the epoch (UTC).
+	// This is later than scheduledTimestamp for catch-up invocations.
+	ActualTimestamp uint64
+	// True if this invocation is catching up on an occurrence that was
+	// missed while the provider was down or a previous invocation was slow
+	CatchUp bool
+	// The cron expression of the schedule that fired, or its `at` or `every` timer
+	Expression string
+	// Name of the link whose schedule fired
+	LinkName string
+	// Name of the schedule that fired. A link with a single unnamed schedule
+	// uses the name "default"
+	ScheduleName string
+	// Time the occurrence was scheduled for, in milliseconds since the epoch (UTC)
+	ScheduledTimestamp uint64
+	// Number of this invocation of the schedule, starting at 1
+	Sequence uint64
+}
+
+// MEncode serialize a CronEvent using msgpack
+func (o *CronEvent) MEncode(encoder msgpack.Writer) error {
+	encoder.WriteMapSize(7)
+	encoder.WriteString("actualTimestamp")
+	encoder.WriteUint64(o.ActualTimestamp)
+	encoder.WriteString("catchUp")
+	encoder.WriteBool(o.CatchUp)
+	encoder.WriteString("expression")
+	encoder.WriteString(o.Expression)
+	encoder.WriteString("linkName")
+	encoder.WriteString(o.LinkName)
+	encoder.WriteString("scheduleName")
+	encoder.WriteString(o.ScheduleName)
+	encoder.WriteString("scheduledTimestamp")
+	encoder.WriteUint64(o.ScheduledTimestamp)
+	encoder.WriteString("sequence")
+	encoder.WriteUint64(o.Sequence)
+
+	return encoder.CheckError()
+}
+
+// MDecodeCronEvent deserializes a CronEvent using msgpack
+func MDecodeCronEvent(d *msgpack.Decoder) (CronEvent, error) {
+	var val CronEvent
+	isNil, err := d.IsNextNil()
+	if err != nil || isNil {
+		return val, err
+	}
+	size, err := d.ReadMapSize()
+	if err != nil {
+		return val, err
+	}
+	for i := uint32(0); i < size; i++ {
+		field, err := d.ReadString()
+		if err != nil {
+			return val, err
+		}
+		switch field {
+		case "actualTimestamp":
+			val.ActualTimestamp, err = d.ReadUint64()
+		case "catchUp":
+			val.CatchUp, err = d.ReadBool()
+		case "expression":
+			val.Expression, err = d.ReadString()
+		case "linkName":
+			val.LinkName, err = d.ReadString()
+		case "scheduleName":
+			val.ScheduleName, err = d.ReadString()
+		case "scheduledTimestamp":
+			val.ScheduledTimestamp, err = d.ReadUint64()
+		case "sequence":
+			val.Sequence, err = d.ReadUint64()
+		default:
+			err = d.Skip()
+		}
+		if err != nil {
+			return val, err
+		}
+	}
+	return val, nil
+}
+
+// Describes a schedule created by an actor
+type ScheduleInfo struct {
+	// The cron expression of the schedule, or its at or every timer
+	Expression string
+	// Name of the schedule
+	Name string
+	// Time of the next occurrence, in milliseconds since the epoch (UTC).
+	// Not set if the schedule has no more occurrences
+	NextTimestamp uint64
+}
+
+// MEncode serialize a ScheduleInfo using msgpack
+func (o *ScheduleInfo) MEncode(encoder msgpack.Writer) error {
+	encoder.WriteMapSize(3)
+	encoder.WriteString("expression")
+	encoder.WriteString(o.Expression)
+	encoder.WriteString("name")
+	encoder.WriteString(o.Name)
+	encoder.WriteString("nextTimestamp")
+	encoder.WriteUint64(o.NextTimestamp)
+
+	return encoder.CheckError()
+}
+
+// MDecodeScheduleInfo deserializes a ScheduleInfo using msgpack
+func MDecodeScheduleInfo(d *msgpack.Decoder) (ScheduleInfo, error) {
+	var val ScheduleInfo
+	isNil, err := d.IsNextNil()
+	if err != nil || isNil {
+		return val, err
+	}
+	size, err := d.ReadMapSize()
+	if err != nil {
+		return val, err
+	}
+	for i := uint32(0); i < size; i++ {
+		field, err := d.ReadString()
+		if err != nil {
+			return val, err
+		}
+		switch field {
+		case "expression":
+			val.Expression, err = d.ReadString()
+		case "name":
+			val.Name, err = d.ReadString()
+		case "nextTimestamp":
+			val.NextTimestamp, err = d.ReadUint64()
+		default:
+			err = d.Skip()
+		}
+		if err != nil {
+			return val, err
+		}
+	}
+	return val, nil
+}
+
+// A list of schedules
+type ScheduleInfos []ScheduleInfo
+
+// MEncode serialize a ScheduleInfos using msgpack
+func (o *ScheduleInfos) MEncode(encoder msgpack.Writer) error {
+	encoder.WriteArraySize(uint32(len(*o)))
+	for _, item_o := range *o {
+		item_o.MEncode(encoder)
+	}
+	return encoder.CheckError()
+}
+
+// MDecodeScheduleInfos deserializes a ScheduleInfos using msgpack
+func MDecodeScheduleInfos(d *msgpack.Decoder) (ScheduleInfos, error) {
+	isNil, err := d.IsNextNil()
+	if err != nil || isNil {
+		return make([]ScheduleInfo, 0), err
+	}
+	size, err := d.ReadArraySize()
+	if err != nil {
+		return make([]ScheduleInfo, 0), err
+	}
+	val := make([]ScheduleInfo, size)
+	for i := uint32(0); i < size; i++ {
+		item, err := MDecodeScheduleInfo(d)
+		if err != nil {
+			return val, err
+		}
+		val[i] = item
+	}
+	return val, nil
+}
+
+// A schedule created by an actor. Exactly one of expression, at and
+// every must be set; they are written the same way as the link values
+// of the same names.
+type ScheduleRequest struct {
+	// An RFC 3339 time to fire once at
+	At string
+	// A duration to fire at intervals of, like "90s"
+	Every string
+	// A full cron expression
+	Expression string
+	// Name of the schedule, delivered as scheduleName in each CronEvent.
+	// It must not be the name of one of the link's schedules.
+	Name string
+	// An RFC 3339 time for the first occurrence of an every timer
+	StartAt string
+	// IANA time zone the cron expression is evaluated in. Defaults to UTC
+	Timezone string
+}
+
+// MEncode serialize a ScheduleRequest using msgpack
+func (o *ScheduleRequest) MEncode(encoder msgpack.Writer) error {
+	encoder.WriteMapSize(6)
+	encoder.WriteString("at")
+	encoder.WriteString(o.At)
+	encoder.WriteString("every")
+	encoder.WriteString(o.Every)
+	encoder.WriteString("expression")
+	encoder.WriteString(o.Expression)
+	encoder.WriteString("name")
+	encoder.WriteString(o.Name)
+	encoder.WriteString("startAt")
+	encoder.WriteString(o.StartAt)
+	encoder.WriteString("timezone")
+	encoder.WriteString(o.Timezone)
+
+	return encoder.CheckError()
+}
+
+// MDecodeScheduleRequest deserializes a ScheduleRequest using msgpack
+func MDecodeScheduleRequest(d *msgpack.Decoder) (ScheduleRequest, error) {
+	var val ScheduleRequest
+	isNil, err := d.IsNextNil()
+	if err != nil || isNil {
+		return val, err
+	}
+	size, err := d.ReadMapSize()
+	if err != nil {
+		return val, err
+	}
+	for i := uint32(0); i < size; i++ {
+		field, err := d.ReadString()
+		if err != nil {
+			return val, err
+		}
+		switch field {
+		case "at":
+			val.At, err = d.ReadString()
+		case "every":
+			val.Every, err = d.ReadString()
+		case "expression":
+			val.Expression, err = d.ReadString()
+		case "name":
+			val.Name, err = d.ReadString()
+		case "startAt":
+			val.StartAt, err = d.ReadString()
+		case "timezone":
+			val.Timezone, err = d.ReadString()
+		default:
+			err = d.Skip()
+		}
+		if err != nil {
+			return val, err
+		}
+	}
+	return val, nil
+}
+
 // The Cron service has a single method, timed_invoke, which
 // invokes an actor after a specified interval
 type Cron interface {
 	// Invoked on an actor on the interval specified in the link
-	TimedInvoke(ctx *actor.Context, arg uint64) error
+	TimedInvoke(ctx *actor.Context, arg CronEvent) error
 }
 
 // CronHandler is called by an actor during `main` to generate a dispatch handler
@@ -35,7 +285,7 @@ func (r *CronReceiver) Dispatch(ctx *actor.Context, svc interface{}, message *ac
 		{
 
 			d := msgpack.NewDecoder(message.Arg)
-			value, err_ := d.ReadUint64()
+			value, err_ := MDecodeCronEvent(&d)
 			if err_ != nil {
 				return nil, err_
 			}
@@ -65,19 +315,112 @@ func NewActorCronSender(actor_id string) *CronSender {
 }
 
 // Invoked on an actor on the interval specified in the link
-func (s *CronSender) TimedInvoke(ctx *actor.Context, arg uint64) error {
+func (s *CronSender) TimedInvoke(ctx *actor.Context, arg CronEvent) error {
 
 	var sizer msgpack.Sizer
 	size_enc := &sizer
-	size_enc.WriteUint64(arg)
+	arg.MEncode(size_enc)
 	buf := make([]byte, sizer.Len())
 
 	var encoder = msgpack.NewEncoder(buf)
 	enc := &encoder
-	enc.WriteUint64(arg)
+	arg.MEncode(enc)
 
 	s.transport.Send(ctx, actor.Message{Method: "Cron.TimedInvoke", Arg: buf})
 	return nil
 }
 
+// The CronScheduler service lets an actor create and remove its own
+// schedules at runtime, in addition to those in its link. Each occurrence
+// of a schedule is delivered to the actor with TimedInvoke.
+type CronScheduler interface {
+	// Creates a schedule for the calling actor, replacing any schedule of
+	// the actor with the same name
+	Schedule(ctx *actor.Context, arg ScheduleRequest) (*ScheduleInfo, error)
+	// Removes one of the calling actor's schedules, by name.
+	// Returns true if the schedule existed
+	Cancel(ctx *actor.Context, arg string) (bool, error)
+	// Returns the schedules created by the calling actor, sorted by name
+	ListSchedules(ctx *actor.Context) (*ScheduleInfos, error)
+}
+
+// CronSchedulerContractId returns the capability contract id for this interface
+func CronSchedulerContractId() string { return "wasmcloud:example:cron" }
+
+// CronSchedulerSender sends messages to a CronScheduler service
+// The CronScheduler service lets an actor create and remove its own
+// schedules at runtime, in addition to those in its link. Each occurrence
+// of a schedule is delivered to the actor with TimedInvoke.
+type CronSchedulerSender struct{ transport actor.Transport }
+
+// NewProviderCronScheduler constructs a client for sending to a CronScheduler provider
+// implementing the 'wasmcloud:example:cron' capability contract, with the "default" link
+func NewProviderCronScheduler() *CronSchedulerSender {
+	transport := actor.ToProvider("wasmcloud:example:cron", "default")
+	return &CronSchedulerSender{transport: transport}
+}
+
+// NewProviderCronSchedulerLink constructs a client for sending to a CronScheduler provider
+// implementing the 'wasmcloud:example:cron' capability contract, with the specified link name
+func NewProviderCronSchedulerLink(linkName string) *CronSchedulerSender {
+	transport := actor.ToProvider("wasmcloud:example:cron", linkName)
+	return &CronSchedulerSender{transport: transport}
+}
+
+// Creates a schedule for the calling actor, replacing any schedule of
+// the actor with the same name
+func (s *CronSchedulerSender) Schedule(ctx *actor.Context, arg ScheduleRequest) (*ScheduleInfo, error) {
+
+	var sizer msgpack.Sizer
+	size_enc := &sizer
+	arg.MEncode(size_enc)
+	buf := make([]byte, sizer.Len())
+
+	var encoder = msgpack.NewEncoder(buf)
+	enc := &encoder
+	arg.MEncode(enc)
+
+	out_buf, _ := s.transport.Send(ctx, actor.Message{Method: "CronScheduler.Schedule", Arg: buf})
+	d := msgpack.NewDecoder(out_buf)
+	resp, err_ := MDecodeScheduleInfo(&d)
+	if err_ != nil {
+		return nil, err_
+	}
+	return &resp, nil
+}
+
+// Removes one of the calling actor's schedules, by name.
+// Returns true if the schedule existed
+func (s *CronSchedulerSender) Cancel(ctx *actor.Context, arg string) (bool, error) {
+
+	var sizer msgpack.Sizer
+	size_enc := &sizer
+	size_enc.WriteString(arg)
+	buf := make([]byte, sizer.Len())
+
+	var encoder = msgpack.NewEncoder(buf)
+	enc := &encoder
+	enc.WriteString(arg)
+
+	out_buf, _ := s.transport.Send(ctx, actor.Message{Method: "CronScheduler.Cancel", Arg: buf})
+	d := msgpack.NewDecoder(out_buf)
+	resp, err_ := d.ReadBool()
+	if err_ != nil {
+		return false, err_
+	}
+	return resp, nil
+}
+
+// Returns the schedules created by the calling actor, sorted by name
+func (s *CronSchedulerSender) ListSchedules(ctx *actor.Context) (*ScheduleInfos, error) {
+	buf := make([]byte, 0)
+	out_buf, _ := s.transport.Send(ctx, actor.Message{Method: "CronScheduler.ListSchedules", Arg: buf})
+	d := msgpack.NewDecoder(out_buf)
+	resp, err_ := MDecodeScheduleInfos(&d)
+	if err_ != nil {
+		return nil, err_
+	}
+	return &resp, nil
+}
+
 // This file is generated automatically using wasmcloud/weld-codegen 0.6.0
@@ -1,10 +1,32 @@
 //! cron capability provider
 //!
-use std::{collections::HashMap, str::FromStr, time::Duration};
+use std::{collections::HashMap, convert::Infallible, str::FromStr, sync::Arc, time::Duration};
 
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use cron::Schedule;
-use wasmbus_rpc::provider::prelude::*;
-use wasmcloud_interface_cron::{Cron, CronSender};
+use tokio::{sync::RwLock, task::JoinHandle};
+use wasmbus_rpc::{
+    core::{HealthCheckRequest, HealthCheckResponse},
+    provider::prelude::*,
+};
+use wasmcloud_interface_cron::{
+    Cron, CronEvent, CronScheduler, CronSchedulerReceiver, CronSender, ScheduleInfo, ScheduleInfos,
+    ScheduleRequest,
+};
+
+mod metrics;
+mod retry;
+mod schedules;
+mod state;
+mod ticker;
+mod timer;
+use metrics::{JobMetrics, Metrics};
+use retry::{DeadLetter, DeadLetterLog, RetryPolicy};
+use schedules::ScheduleStore;
+use state::StateStore;
+use ticker::{Clock, SystemClock, Tick, Ticker};
+use timer::Timer;
 
 const EXPRESSION: &str = "expression";
 const SECOND: &str = "second";
@@ -15,9 +37,26 @@ const MONTH: &str = "month";
 const DAY_OF_WEEK: &str = "day_of_week";
 const YEAR: &str = "year";
 const DEFAULT_CRON_VALUE: &str = "*";
+const TIMEZONE: &str = "timezone";
+const MISFIRE_POLICY: &str = "misfire_policy";
+const SCHEDULE_PREFIX: &str = "schedule.";
+const DEFAULT_SCHEDULE_NAME: &str = "default";
+const RETRY_COUNT: &str = "retry_count";
+const RETRY_BACKOFF: &str = "retry_backoff";
+const INVOKE_TIMEOUT: &str = "invoke_timeout";
+const CONCURRENCY: &str = "concurrency";
+const AT: &str = "at";
+const EVERY: &str = "every";
+const START_AT: &str = "start_at";
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    provider_main(CronProvider::default(), Some("Cron".to_string()))?;
+    let provider = CronProvider {
+        state: StateStore::load(),
+        schedules: ScheduleStore::load(),
+        dead_letters: DeadLetterLog::from_env(),
+        ..Default::default()
+    };
+    provider_main(provider, Some("Cron".to_string()))?;
 
     eprintln!("cron provider exiting");
     Ok(())
@@ -25,9 +64,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 /// cron capability provider implementation
 #[derive(Default, Clone, Provider)]
-// TODO: Map from actor ID to join handle
+#[services(CronScheduler)]
 struct CronProvider {
-    // handles: HashMap<String, JoinHandle<()>>,
+    /// Running cron jobs, keyed by the ID of the linked actor
+    jobs: Arc<RwLock<HashMap<String, ActorJobs>>>,
+    /// When each job last fired, persisted across provider restarts
+    state: StateStore,
+    /// Schedules created by actors, persisted across provider restarts
+    schedules: ScheduleStore,
+    /// Where invocations that failed after all their retries are recorded
+    dead_letters: DeadLetterLog,
+    /// What each job has done, reported in health checks
+    metrics: Metrics,
 }
 
 /// use default implementations of provider message handlers
@@ -35,49 +83,631 @@ impl ProviderDispatch for CronProvider {}
 #[async_trait]
 impl ProviderHandler for CronProvider {
     async fn put_link(&self, ld: &LinkDefinition) -> RpcResult<bool> {
-        let ld = ld.clone();
-        let schedule = if let Ok(schedule) = cron_schedule(&ld.values) {
-            schedule
+        let schedules = if let Ok(schedules) = cron_schedules(&ld.values) {
+            schedules
         } else {
             // If we fail to parse the schedule, deny link definition
             return Ok(false);
         };
-        tokio::spawn(async move {
-            let mut prev = chrono::Utc::now();
-            loop {
-                // Get the next cron occurrence
-                // SAFETY: If we fail to get the next interval, we can't continue to tick, so this task can panic
-                let next = schedule.upcoming(chrono::Utc).next().unwrap();
-                tracing::debug!("Next invocation for actor {} at {next}", ld.actor_id);
-
-                // Avoid triggering multiple invocations on the same occurrence
-                if next == prev {
+        let timezone = if let Ok(timezone) = cron_timezone(&ld.values) {
+            timezone
+        } else {
+            // If the timezone isn't a known IANA zone name, deny link definition
+            return Ok(false);
+        };
+        let misfire_policy = if let Ok(misfire_policy) = cron_misfire_policy(&ld.values) {
+            misfire_policy
+        } else {
+            // If the misfire policy is unknown, deny link definition
+            return Ok(false);
+        };
+        let retry_policy = if let Ok(retry_policy) = cron_retry_policy(&ld.values) {
+            retry_policy
+        } else {
+            // If the retry settings are invalid, deny link definition
+            return Ok(false);
+        };
+        let concurrency = if let Ok(concurrency) = cron_concurrency_policy(&ld.values) {
+            concurrency
+        } else {
+            // If the concurrency policy is unknown, deny link definition
+            return Ok(false);
+        };
+        let policy = JobPolicy {
+            misfire_policy,
+            concurrency,
+            retry_policy,
+        };
+        // Each named schedule runs as an independent job
+        let mut jobs = ActorJobs {
+            ld: ld.clone(),
+            policy,
+            link: schedules
+                .into_iter()
+                .map(|(name, timer)| {
+                    let job = CronJob {
+                        name: name.clone(),
+                        timer,
+                        timezone,
+                        policy,
+                    };
+                    (name, self.spawn_job(ld, job))
+                })
+                .collect(),
+            actor: HashMap::new(),
+        };
+
+        // Hold the lock while restarting the actor's own schedules, so they can't change
+        let mut all_jobs = self.jobs.write().await;
+        for request in self.schedules.actor(&ld.actor_id).await {
+            if jobs.link.contains_key(&request.name) {
+                tracing::warn!(
+                    "Not starting schedule '{}' of actor {}, the link has a schedule with the \
+                     same name",
+                    request.name,
+                    ld.actor_id
+                );
+                continue;
+            }
+            match self.spawn_actor_job(ld, policy, &request) {
+                Ok(handle) => {
+                    jobs.actor.insert(request.name, handle);
+                }
+                Err(e) => tracing::error!(
+                    "Error starting schedule '{}' of actor {}: {e}",
+                    request.name,
+                    ld.actor_id
+                ),
+            }
+        }
+
+        // Replace (and stop) any jobs left over from a previous link to the same actor
+        let actor_id = &ld.actor_id;
+        if let Some(previous) = all_jobs.insert(actor_id.clone(), jobs) {
+            tracing::info!("Replacing existing cron jobs for actor {actor_id}");
+            previous.abort();
+        }
+
+        Ok(true)
+    }
+
+    /// Stops the cron jobs for an actor when its link is removed
+    async fn delete_link(&self, actor_id: &str) {
+        if let Some(jobs) = self.jobs.write().await.remove(actor_id) {
+            tracing::debug!("Stopping cron jobs for actor {actor_id}");
+            jobs.abort();
+        }
+        // An unlinked actor shouldn't catch up on occurrences if it's linked again later
+        self.state.forget_actor(actor_id).await;
+        self.schedules.forget_actor(actor_id).await;
+        self.metrics.forget_actor(actor_id).await;
+    }
+
+    /// Reports the invocation counters of each job
+    async fn health_request(&self, _arg: &HealthCheckRequest) -> RpcResult<HealthCheckResponse> {
+        Ok(HealthCheckResponse {
+            healthy: true,
+            message: Some(self.metrics.report().await),
+        })
+    }
+
+    /// Stops all running cron jobs
+    async fn shutdown(&self) -> Result<(), Infallible> {
+        let mut jobs = self.jobs.write().await;
+        for (actor_id, jobs) in jobs.drain() {
+            tracing::debug!("Stopping cron jobs for actor {actor_id}");
+            jobs.abort();
+        }
+        Ok(())
+    }
+}
+
+/// Schedules created by actors at runtime
+#[async_trait]
+impl CronScheduler for CronProvider {
+    async fn schedule(&self, ctx: &Context, arg: &ScheduleRequest) -> RpcResult<ScheduleInfo> {
+        let actor_id = calling_actor(ctx)?;
+        if arg.name.is_empty() {
+            return Err(RpcError::InvalidParameter(
+                "missing schedule name".to_string(),
+            ));
+        }
+        let (timer, timezone) = actor_schedule_timer(arg).map_err(RpcError::InvalidParameter)?;
+        // Keep the start of an interval timer, so it doesn't move when the provider restarts
+        let mut request = arg.clone();
+        if let Timer::Every { start_at, .. } = &timer {
+            request
+                .start_at
+                .get_or_insert_with(|| start_at.to_rfc3339());
+        }
+
+        let mut all_jobs = self.jobs.write().await;
+        let jobs = all_jobs.get_mut(actor_id).ok_or_else(|| {
+            RpcError::InvalidParameter(format!("actor {actor_id} is not linked to the provider"))
+        })?;
+        if jobs.link.contains_key(&request.name) {
+            return Err(RpcError::InvalidParameter(format!(
+                "'{}' is the name of a schedule in the link",
+                request.name
+            )));
+        }
+        self.schedules.insert(actor_id, request.clone()).await;
+        let handle = self
+            .spawn_actor_job(&jobs.ld, jobs.policy, &request)
+            .map_err(RpcError::InvalidParameter)?;
+        if let Some(previous) = jobs.actor.insert(request.name.clone(), handle) {
+            tracing::info!("Replacing schedule '{}' of actor {actor_id}", request.name);
+            previous.abort();
+        }
+        Ok(schedule_info(&request.name, &timer, timezone))
+    }
+
+    async fn cancel<TS: ToString + ?Sized + std::marker::Sync>(
+        &self,
+        ctx: &Context,
+        arg: &TS,
+    ) -> RpcResult<bool> {
+        let actor_id = calling_actor(ctx)?;
+        let name = arg.to_string();
+        if let Some(handle) = self
+            .jobs
+            .write()
+            .await
+            .get_mut(actor_id)
+            .and_then(|jobs| jobs.actor.remove(&name))
+        {
+            handle.abort();
+        }
+        Ok(self.schedules.remove(actor_id, &name, None).await)
+    }
+
+    async fn list_schedules(&self, ctx: &Context) -> RpcResult<ScheduleInfos> {
+        let actor_id = calling_actor(ctx)?;
+        Ok(self
+            .schedules
+            .actor(actor_id)
+            .await
+            .iter()
+            .filter_map(|request| {
+                let (timer, timezone) = actor_schedule_timer(request).ok()?;
+                Some(schedule_info(&request.name, &timer, timezone))
+            })
+            .collect())
+    }
+}
+
+impl CronProvider {
+    /// Starts a job that invokes the actor on each occurrence of its timer
+    fn spawn_job(&self, ld: &LinkDefinition, job: CronJob) -> JoinHandle<()> {
+        tokio::spawn(run_job(
+            ld.clone(),
+            job,
+            self.state.clone(),
+            self.dead_letters.clone(),
+            self.metrics.clone(),
+        ))
+    }
+
+    /// Starts the job for a schedule created by an actor. The schedule is removed once it
+    /// has no more occurrences, like a one-shot timer that has fired.
+    fn spawn_actor_job(
+        &self,
+        ld: &LinkDefinition,
+        policy: JobPolicy,
+        request: &ScheduleRequest,
+    ) -> Result<JoinHandle<()>, String> {
+        let (timer, timezone) = actor_schedule_timer(request)?;
+        let job = CronJob {
+            name: request.name.clone(),
+            timer,
+            timezone,
+            policy,
+        };
+        let provider = self.clone();
+        let ld = ld.clone();
+        let request = request.clone();
+        Ok(tokio::spawn(async move {
+            run_job(
+                ld.clone(),
+                job,
+                provider.state.clone(),
+                provider.dead_letters.clone(),
+                provider.metrics.clone(),
+            )
+            .await;
+            // The schedule may have been replaced while the job was finishing
+            if provider
+                .schedules
+                .remove(&ld.actor_id, &request.name, Some(&request))
+                .await
+            {
+                if let Some(jobs) = provider.jobs.write().await.get_mut(&ld.actor_id) {
+                    jobs.actor.remove(&request.name);
+                }
+            }
+        }))
+    }
+}
+
+/// Cron jobs for a linked actor
+struct ActorJobs {
+    ld: LinkDefinition,
+    /// Policies of the link, which also apply to the actor's own schedules
+    policy: JobPolicy,
+    /// Jobs for the link's schedules, by schedule name
+    link: HashMap<String, JoinHandle<()>>,
+    /// Jobs for schedules created by the actor, by schedule name
+    actor: HashMap<String, JoinHandle<()>>,
+}
+
+impl ActorJobs {
+    fn abort(&self) {
+        self.link
+            .values()
+            .chain(self.actor.values())
+            .for_each(JoinHandle::abort);
+    }
+}
+
+/// Settings for a single cron job, parsed from link values
+struct CronJob {
+    name: String,
+    timer: Timer,
+    timezone: Tz,
+    policy: JobPolicy,
+}
+
+/// How a cron job handles missed, overlapping and failed invocations
+#[derive(Clone, Copy, Debug, Default)]
+struct JobPolicy {
+    misfire_policy: MisfirePolicy,
+    concurrency: ConcurrencyPolicy,
+    retry_policy: RetryPolicy,
+}
+
+/// What to do with occurrences that were missed while the provider was down
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum MisfirePolicy {
+    /// Drop missed occurrences and wait for the next one
+    #[default]
+    Skip,
+    /// Invoke the actor once, for the most recent missed occurrence
+    FireOnce,
+    /// Invoke the actor for every missed occurrence, oldest first
+    FireAll,
+}
+
+impl FromStr for MisfirePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(MisfirePolicy::Skip),
+            "fire_once" => Ok(MisfirePolicy::FireOnce),
+            "fire_all" => Ok(MisfirePolicy::FireAll),
+            _ => Err(format!("unknown misfire policy '{s}'")),
+        }
+    }
+}
+
+/// What to do when an occurrence comes due while the previous invocation of the same
+/// job is still running
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ConcurrencyPolicy {
+    /// Skip the new occurrence
+    #[default]
+    Forbid,
+    /// Invoke the actor for the new occurrence alongside the running invocation
+    Allow,
+    /// Abort the running invocation and invoke the actor for the new occurrence
+    Replace,
+}
+
+impl FromStr for ConcurrencyPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "forbid" => Ok(ConcurrencyPolicy::Forbid),
+            "allow" => Ok(ConcurrencyPolicy::Allow),
+            "replace" => Ok(ConcurrencyPolicy::Replace),
+            _ => Err(format!("unknown concurrency policy '{s}'")),
+        }
+    }
+}
+
+/// Invokes the linked actor on every occurrence of the timer, until the task is aborted.
+/// Cron occurrences are computed in the local time of `timezone`.
+async fn run_job(
+    ld: LinkDefinition,
+    job: CronJob,
+    state: StateStore,
+    dead_letters: DeadLetterLog,
+    metrics: Metrics,
+) {
+    let CronJob {
+        name,
+        timer,
+        timezone,
+        policy:
+            JobPolicy {
+                misfire_policy,
+                concurrency,
+                retry_policy,
+            },
+    } = job;
+    let key = state::job_key(&ld.actor_id, &name);
+
+    // Resume from the last occurrence fired before a restart, if there is one
+    let start = match state.get(&key).await.last_fired {
+        Some(fired) if misfire_policy != MisfirePolicy::Skip => fired,
+        _ => SystemClock.now(),
+    };
+    let invoker = Arc::new(Invoker {
+        expression: timer.to_string(),
+        name,
+        metrics: metrics.job(&key).await,
+        key,
+        ld,
+        state,
+        retry_policy,
+        dead_letters,
+    });
+    let mut ticker = Ticker::new(timer, timezone, SystemClock, start);
+    let mut running = Running::default();
+    loop {
+        let (occurrences, catch_up) = match ticker.tick().await {
+            // Handle occurrences that passed while the provider was down
+            Tick::Missed(missed) => {
+                tracing::warn!(
+                    "Missed {} invocation(s) of schedule '{}' for actor {}, misfire policy is \
+                     {misfire_policy:?}",
+                    missed.len(),
+                    invoker.name,
+                    invoker.ld.actor_id
+                );
+                match misfire_policy {
+                    MisfirePolicy::Skip => continue,
+                    MisfirePolicy::FireOnce => (missed[missed.len() - 1..].to_vec(), true),
+                    MisfirePolicy::FireAll => (missed, true),
+                }
+            }
+            Tick::Due(occurrence) => (vec![occurrence], false),
+            Tick::Finished => {
+                tracing::info!(
+                    "No more occurrences of schedule '{}' for actor {}, stopping",
+                    invoker.name,
+                    invoker.ld.actor_id
+                );
+                running.wait().await;
+                return;
+            }
+        };
+
+        if running.is_busy() {
+            match concurrency {
+                ConcurrencyPolicy::Forbid => {
+                    tracing::warn!(
+                        "Skipping {} invocation(s) of schedule '{}' for actor {}, the previous \
+                         invocation is still running",
+                        occurrences.len(),
+                        invoker.name,
+                        invoker.ld.actor_id
+                    );
+                    JobMetrics::add(&invoker.metrics.skipped, occurrences.len() as u64);
                     continue;
                 }
+                ConcurrencyPolicy::Allow => {}
+                ConcurrencyPolicy::Replace => {
+                    tracing::warn!(
+                        "Aborting running invocation of schedule '{}' for actor {}, a new \
+                         occurrence is due",
+                        invoker.name,
+                        invoker.ld.actor_id
+                    );
+                    JobMetrics::add(&invoker.metrics.replaced, running.abort() as u64);
+                }
+            }
+        }
+        // Catch-up occurrences are fired one after another, as a single invocation task
+        let invoker = invoker.clone();
+        running.push(tokio::spawn(async move {
+            for occurrence in occurrences {
+                invoker.fire(occurrence, catch_up).await;
+            }
+        }));
+    }
+}
+
+/// Invocation tasks of a job that may still be running. Dropping it aborts them, so
+/// stopping a job also stops its invocations.
+#[derive(Default)]
+struct Running(Vec<JoinHandle<()>>);
+
+impl Running {
+    fn push(&mut self, handle: JoinHandle<()>) {
+        self.0.push(handle);
+    }
 
-                // Calculate the duration until the next occurrence
-                let duration = next - chrono::Utc::now();
+    /// Returns true if any invocation is still running, forgetting those that finished
+    fn is_busy(&mut self) -> bool {
+        self.0.retain(|handle| !handle.is_finished());
+        !self.0.is_empty()
+    }
 
-                // Sleep for the duration
-                tokio::time::sleep(Duration::from_secs(duration.num_seconds() as u64)).await;
+    /// Aborts all running invocations and returns how many there were
+    fn abort(&mut self) -> usize {
+        let count = self.0.len();
+        self.0.drain(..).for_each(|handle| handle.abort());
+        count
+    }
 
-                // let coarse_time = (chrono::Utc::now().timestamp() / 10) * 10;
-                let coarse_time = chrono::Utc::now().timestamp();
+    /// Waits for all running invocations to finish
+    async fn wait(&mut self) {
+        for handle in self.0.drain(..) {
+            let _ = handle.await;
+        }
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
 
-                // Call your function here
-                if let Err(e) = CronSender::for_actor(&ld)
-                    .timed_invoke(&Context::default(), &(coarse_time as u64))
-                    .await
-                {
-                    tracing::error!("Error time invoking actor {} : {e}", ld.actor_id)
-                };
+/// Sends the `CronEvent` for each occurrence of a job to the linked actor
+struct Invoker {
+    ld: LinkDefinition,
+    name: String,
+    expression: String,
+    /// Key of the job in the state store
+    key: String,
+    state: StateStore,
+    retry_policy: RetryPolicy,
+    dead_letters: DeadLetterLog,
+    metrics: Arc<JobMetrics>,
+}
 
-                prev = next;
+impl Invoker {
+    /// Invokes the actor for a single occurrence, retrying according to the retry policy,
+    /// and records the occurrence as fired. The state file is written once, when the
+    /// occurrence is recorded.
+    async fn fire(&self, occurrence: DateTime<Tz>, catch_up: bool) {
+        let sequence = self
+            .state
+            .update_unsaved(&self.key, |job_state| job_state.sequence += 1)
+            .await
+            .sequence;
+        let mut event = CronEvent {
+            link_name: self.ld.link_name.clone(),
+            schedule_name: self.name.clone(),
+            expression: self.expression.clone(),
+            scheduled_timestamp: occurrence.timestamp_millis() as u64,
+            actual_timestamp: SystemClock.now().timestamp_millis() as u64,
+            sequence,
+            catch_up,
+        };
+        let sender = CronSender::for_actor(&self.ld);
+        if let Some(timeout) = self.retry_policy.invoke_timeout {
+            sender.set_timeout(timeout);
+        }
+        let mut attempt = 1;
+        loop {
+            match sender.timed_invoke(&Context::default(), &event).await {
+                Ok(()) => {
+                    JobMetrics::add(&self.metrics.invocations, 1);
+                    break;
+                }
+                Err(e) if attempt <= self.retry_policy.retry_count => {
+                    let backoff = self.retry_policy.backoff(attempt);
+                    tracing::warn!(
+                        "Error time invoking actor {} (attempt {attempt}), retrying in \
+                         {backoff:?} : {e}",
+                        self.ld.actor_id
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                    event.actual_timestamp = SystemClock.now().timestamp_millis() as u64;
+                }
+                Err(e) => {
+                    JobMetrics::add(&self.metrics.failures, 1);
+                    self.dead_letters
+                        .write(&DeadLetter {
+                            actor_id: &self.ld.actor_id,
+                            link_name: &self.ld.link_name,
+                            schedule_name: &self.name,
+                            expression: &self.expression,
+                            scheduled_timestamp: event.scheduled_timestamp,
+                            sequence: event.sequence,
+                            attempts: attempt,
+                            error: e.to_string(),
+                        })
+                        .await;
+                    break;
+                }
             }
-        });
+        }
+        // Invocations running at once may finish out of order, so keep the latest
+        let fired = occurrence.with_timezone(&Utc);
+        self.state
+            .update(&self.key, |job_state| {
+                job_state.last_fired = job_state.last_fired.max(Some(fired))
+            })
+            .await;
+    }
+}
 
-        Ok(true)
+/// Returns the ID of the actor that sent a CronScheduler request
+fn calling_actor(ctx: &Context) -> RpcResult<&str> {
+    ctx.actor
+        .as_deref()
+        .ok_or_else(|| RpcError::InvalidParameter("missing calling actor".to_string()))
+}
+
+/// Parses the timer and timezone of a schedule created by an actor. Its fields are parsed
+/// the same way as the link values of the same names, by [cron_timer] and [cron_timezone].
+fn actor_schedule_timer(request: &ScheduleRequest) -> Result<(Timer, Tz), String> {
+    let values: HashMap<String, String> = [
+        (EXPRESSION, &request.expression),
+        (AT, &request.at),
+        (EVERY, &request.every),
+        (START_AT, &request.start_at),
+        (TIMEZONE, &request.timezone),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| Some((key.to_string(), value.clone()?)))
+    .collect();
+    if [EXPRESSION, AT, EVERY]
+        .iter()
+        .filter(|key| values.contains_key(**key))
+        .count()
+        != 1
+    {
+        return Err(format!(
+            "exactly one of {EXPRESSION}, {AT} and {EVERY} must be set"
+        ));
+    }
+    let timer = cron_timer(&values)?;
+    let timezone = cron_timezone(&values).map_err(|e| e.to_string())?;
+    Ok((timer, timezone))
+}
+
+/// Describes a schedule created by an actor, with its next occurrence after now
+fn schedule_info(name: &str, timer: &Timer, timezone: Tz) -> ScheduleInfo {
+    ScheduleInfo {
+        name: name.to_string(),
+        expression: timer.to_string(),
+        next_timestamp: timer
+            .after(&SystemClock.now().with_timezone(&timezone))
+            .next()
+            .map(|next| next.timestamp_millis() as u64),
+    }
+}
+
+/// Parses the named schedules of a link.
+///
+/// Each `schedule.<name>` value is a full cron expression for a schedule called `<name>`.
+/// If there are none, the link has a single schedule called "default", parsed by
+/// [cron_timer]. Schedules are returned sorted by name.
+fn cron_schedules(ld_values: &HashMap<String, String>) -> Result<Vec<(String, Timer)>, String> {
+    let mut schedules = ld_values
+        .iter()
+        .filter_map(|(key, value)| Some((key.strip_prefix(SCHEDULE_PREFIX)?, value)))
+        .map(|(name, expression)| {
+            if name.is_empty() {
+                return Err(format!("missing schedule name in '{SCHEDULE_PREFIX}'"));
+            }
+            let schedule = Schedule::from_str(expression)
+                .map_err(|e| format!("invalid expression for schedule '{name}': {e}"))?;
+            Ok((name.to_string(), Timer::from(schedule)))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if schedules.is_empty() {
+        schedules.push((DEFAULT_SCHEDULE_NAME.to_string(), cron_timer(ld_values)?));
     }
+    schedules.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(schedules)
 }
 
 /// Parses a cron expression from a HashMap of key-value pairs.
@@ -91,12 +721,12 @@ impl ProviderHandler for CronProvider {
 /// # Arguments
 ///
 /// * `ld_values` - A reference to a HashMap of String keys and values that represent a cron
-/// expression or its individual fields, usually sourced from the [LinkDefinition](LinkDefinition).
+///   expression or its individual fields, usually sourced from the [LinkDefinition](LinkDefinition).
 ///
 /// # Returns
 ///
 /// * If the function successfully parses a cron expression from the input HashMap, it returns
-/// a `Schedule` object that represents the cron schedule.
+///   a `Schedule` object that represents the cron schedule.
 ///
 /// * If the cron expression is invalid, the function returns an `Error` object.
 ///
@@ -132,11 +762,115 @@ fn cron_schedule(ld_values: &HashMap<String, String>) -> Result<Schedule, cron::
     }
 }
 
+/// Parses the timer of a link's default schedule.
+///
+/// An "at" value is an RFC 3339 instant to fire once at. An "every" value is a humantime
+/// interval to fire at, like `90s`, starting from the RFC 3339 instant in "start_at". Without
+/// "start_at", the first occurrence is one interval from now. Either of these takes precedence
+/// over a cron expression, which is otherwise parsed by [cron_schedule].
+fn cron_timer(ld_values: &HashMap<String, String>) -> Result<Timer, String> {
+    let at = ld_values.get(AT);
+    let every = ld_values.get(EVERY);
+    let start_at = ld_values.get(START_AT);
+    match (at, every) {
+        (Some(_), Some(_)) => Err(format!("only one of {AT} and {EVERY} may be set")),
+        (_, None) if start_at.is_some() => Err(format!("{START_AT} requires {EVERY}")),
+        (Some(at), None) => Ok(Timer::At(parse_instant(AT, at)?)),
+        (None, Some(every)) => {
+            let interval = parse_duration(EVERY, every)?;
+            chrono::Duration::from_std(interval)
+                .map_err(|_| format!("{EVERY} '{every}' is too long"))?;
+            let start_at = match start_at {
+                Some(start_at) => parse_instant(START_AT, start_at)?,
+                None => SystemClock.now(),
+            };
+            Ok(Timer::Every { interval, start_at })
+        }
+        (None, None) => cron_schedule(ld_values)
+            .map(Timer::from)
+            .map_err(|e| format!("invalid schedule: {e}")),
+    }
+}
+
+/// Parses the timezone that a cron schedule is evaluated in.
+///
+/// The "timezone" value must be an IANA time zone name, like `Europe/Berlin` or
+/// `America/New_York`. If it is not present, schedules are evaluated in UTC.
+fn cron_timezone(ld_values: &HashMap<String, String>) -> Result<Tz, chrono_tz::ParseError> {
+    ld_values
+        .get(TIMEZONE)
+        .map(|tz| tz.parse())
+        .unwrap_or(Ok(Tz::UTC))
+}
+
+/// Parses how missed occurrences are handled from the "misfire_policy" value,
+/// one of `skip` (the default), `fire_once` or `fire_all`
+fn cron_misfire_policy(ld_values: &HashMap<String, String>) -> Result<MisfirePolicy, String> {
+    ld_values
+        .get(MISFIRE_POLICY)
+        .map(|policy| policy.parse())
+        .unwrap_or(Ok(MisfirePolicy::default()))
+}
+
+/// Parses whether occurrences of a job may overlap from the "concurrency" value,
+/// one of `forbid` (the default), `allow` or `replace`
+fn cron_concurrency_policy(
+    ld_values: &HashMap<String, String>,
+) -> Result<ConcurrencyPolicy, String> {
+    ld_values
+        .get(CONCURRENCY)
+        .map(|policy| policy.parse())
+        .unwrap_or(Ok(ConcurrencyPolicy::default()))
+}
+
+/// Parses how failed invocations are retried from the "retry_count", "retry_backoff"
+/// and "invoke_timeout" values. Durations use humantime syntax, like `500ms` or `1m 30s`.
+fn cron_retry_policy(ld_values: &HashMap<String, String>) -> Result<RetryPolicy, String> {
+    let mut policy = RetryPolicy::default();
+    if let Some(count) = ld_values.get(RETRY_COUNT) {
+        policy.retry_count = count
+            .parse()
+            .map_err(|e| format!("invalid {RETRY_COUNT} '{count}': {e}"))?;
+    }
+    if let Some(backoff) = ld_values.get(RETRY_BACKOFF) {
+        policy.retry_backoff = parse_duration(RETRY_BACKOFF, backoff)?;
+    }
+    if let Some(timeout) = ld_values.get(INVOKE_TIMEOUT) {
+        policy.invoke_timeout = Some(parse_duration(INVOKE_TIMEOUT, timeout)?);
+    }
+    Ok(policy)
+}
+
+/// Parses an RFC 3339 instant, like `2023-06-01T09:00:00Z`, from the link value `key`
+fn parse_instant(key: &str, value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|instant| instant.with_timezone(&Utc))
+        .map_err(|e| format!("invalid {key} '{value}': {e}"))
+}
+
+/// Parses a non-zero humantime duration from the link value `key`
+fn parse_duration(key: &str, value: &str) -> Result<Duration, String> {
+    match humantime::parse_duration(value) {
+        Ok(duration) if !duration.is_zero() => Ok(duration),
+        Ok(_) => Err(format!("{key} must not be zero")),
+        Err(e) => Err(format!("invalid {key} '{value}': {e}")),
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use std::collections::HashMap;
+    use std::{collections::HashMap, time::Duration};
 
-    use crate::cron_schedule;
+    use chrono::{TimeZone, Timelike, Utc};
+    use chrono_tz::Tz;
+
+    use wasmcloud_interface_cron::ScheduleRequest;
+
+    use crate::{
+        actor_schedule_timer, cron_concurrency_policy, cron_misfire_policy, cron_retry_policy,
+        cron_schedule, cron_schedules, cron_timer, cron_timezone, ConcurrencyPolicy, MisfirePolicy,
+        RetryPolicy, Timer,
+    };
 
     #[test]
     fn can_make_scheduler() {
@@ -150,4 +884,216 @@ mod test {
         let scheduler = cron_schedule(&expression);
         assert!(scheduler.is_ok())
     }
+
+    #[test]
+    fn can_parse_timezone() {
+        assert_eq!(cron_timezone(&HashMap::new()).unwrap(), Tz::UTC);
+
+        let values = HashMap::from_iter([("timezone".to_string(), "Europe/Berlin".to_string())]);
+        assert_eq!(cron_timezone(&values).unwrap(), Tz::Europe__Berlin);
+
+        let values = HashMap::from_iter([("timezone".to_string(), "Europe/Nowhere".to_string())]);
+        assert!(cron_timezone(&values).is_err());
+    }
+
+    #[test]
+    fn schedule_follows_local_time_across_dst() {
+        let expression =
+            HashMap::from_iter([("expression".to_string(), "0 0 9 * * * *".to_string())]);
+        let schedule = cron_schedule(&expression).unwrap();
+        let tz = Tz::Europe__Berlin;
+
+        // Europe/Berlin switches from CET to CEST on 2023-03-26
+        let start = tz.with_ymd_and_hms(2023, 3, 24, 12, 0, 0).unwrap();
+        let upcoming: Vec<_> = schedule.after(&start).take(4).collect();
+        assert!(upcoming.iter().all(|t| t.hour() == 9));
+        assert_eq!(upcoming[0].with_timezone(&chrono::Utc).hour(), 8);
+        assert_eq!(upcoming[3].with_timezone(&chrono::Utc).hour(), 7);
+    }
+
+    #[test]
+    fn can_parse_misfire_policy() {
+        assert_eq!(
+            cron_misfire_policy(&HashMap::new()).unwrap(),
+            MisfirePolicy::Skip
+        );
+
+        for (value, policy) in [
+            ("skip", MisfirePolicy::Skip),
+            ("fire_once", MisfirePolicy::FireOnce),
+            ("fire_all", MisfirePolicy::FireAll),
+        ] {
+            let values = HashMap::from_iter([("misfire_policy".to_string(), value.to_string())]);
+            assert_eq!(cron_misfire_policy(&values).unwrap(), policy);
+        }
+
+        let values = HashMap::from_iter([("misfire_policy".to_string(), "later".to_string())]);
+        assert!(cron_misfire_policy(&values).is_err());
+    }
+
+    #[test]
+    fn can_parse_concurrency_policy() {
+        assert_eq!(
+            cron_concurrency_policy(&HashMap::new()).unwrap(),
+            ConcurrencyPolicy::Forbid
+        );
+
+        for (value, policy) in [
+            ("forbid", ConcurrencyPolicy::Forbid),
+            ("allow", ConcurrencyPolicy::Allow),
+            ("replace", ConcurrencyPolicy::Replace),
+        ] {
+            let values = HashMap::from_iter([("concurrency".to_string(), value.to_string())]);
+            assert_eq!(cron_concurrency_policy(&values).unwrap(), policy);
+        }
+
+        let values = HashMap::from_iter([("concurrency".to_string(), "queue".to_string())]);
+        assert!(cron_concurrency_policy(&values).is_err());
+    }
+
+    #[test]
+    fn can_make_named_schedules() {
+        let values = HashMap::from_iter([
+            (
+                "schedule.nightly_cleanup".to_string(),
+                "0 0 3 * * * *".to_string(),
+            ),
+            (
+                "schedule.hourly_report".to_string(),
+                "0 0 * * * * *".to_string(),
+            ),
+            // Ignored when there are named schedules
+            ("expression".to_string(), "1 * * * * * *".to_string()),
+        ]);
+        let schedules = cron_schedules(&values).unwrap();
+        let names: Vec<_> = schedules.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["hourly_report", "nightly_cleanup"]);
+        assert_eq!(schedules[0].1.to_string(), "0 0 * * * * *");
+
+        let expression =
+            HashMap::from_iter([("expression".to_string(), "1 * * * * * *".to_string())]);
+        let schedules = cron_schedules(&expression).unwrap();
+        assert_eq!(schedules.len(), 1);
+        assert_eq!(schedules[0].0, "default");
+
+        let invalid = HashMap::from_iter([("schedule.bad".to_string(), "not cron".to_string())]);
+        assert!(cron_schedules(&invalid).is_err());
+
+        let unnamed = HashMap::from_iter([("schedule.".to_string(), "0 * * * * * *".to_string())]);
+        assert!(cron_schedules(&unnamed).is_err());
+    }
+
+    #[test]
+    fn can_parse_timers() {
+        let values =
+            HashMap::from_iter([("at".to_string(), "2023-06-01T11:00:00+02:00".to_string())]);
+        assert_eq!(
+            cron_timer(&values).unwrap(),
+            Timer::At(Utc.with_ymd_and_hms(2023, 6, 1, 9, 0, 0).unwrap())
+        );
+
+        let values = HashMap::from_iter([
+            ("every".to_string(), "90s".to_string()),
+            ("start_at".to_string(), "2023-06-01T09:00:00Z".to_string()),
+            // Ignored when there is a timer
+            ("expression".to_string(), "1 * * * * * *".to_string()),
+        ]);
+        assert_eq!(
+            cron_timer(&values).unwrap(),
+            Timer::Every {
+                interval: Duration::from_secs(90),
+                start_at: Utc.with_ymd_and_hms(2023, 6, 1, 9, 0, 0).unwrap(),
+            }
+        );
+
+        let values = HashMap::from_iter([("every".to_string(), "1m".to_string())]);
+        assert!(matches!(
+            cron_timer(&values).unwrap(),
+            Timer::Every { interval, .. } if interval == Duration::from_secs(60)
+        ));
+
+        let expression =
+            HashMap::from_iter([("expression".to_string(), "1 * * * * * *".to_string())]);
+        assert!(matches!(cron_timer(&expression).unwrap(), Timer::Cron(_)));
+
+        for invalid in [
+            vec![("at", "tomorrow")],
+            vec![("every", "0s")],
+            vec![("at", "2023-06-01T09:00:00Z"), ("every", "1m")],
+            vec![("start_at", "2023-06-01T09:00:00Z")],
+            vec![("every", "1m"), ("start_at", "now")],
+        ] {
+            let values = invalid
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+            assert!(cron_timer(&values).is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn can_parse_actor_schedules() {
+        let request = ScheduleRequest {
+            name: "visit-reminder".to_string(),
+            at: Some("2023-06-01T09:00:00Z".to_string()),
+            ..Default::default()
+        };
+        let (timer, timezone) = actor_schedule_timer(&request).unwrap();
+        assert_eq!(
+            timer,
+            Timer::At(Utc.with_ymd_and_hms(2023, 6, 1, 9, 0, 0).unwrap())
+        );
+        assert_eq!(timezone, Tz::UTC);
+
+        let request = ScheduleRequest {
+            name: "morning".to_string(),
+            expression: Some("0 0 9 * * * *".to_string()),
+            timezone: Some("Europe/Berlin".to_string()),
+            ..Default::default()
+        };
+        let (timer, timezone) = actor_schedule_timer(&request).unwrap();
+        assert!(matches!(timer, Timer::Cron(_)));
+        assert_eq!(timezone, Tz::Europe__Berlin);
+
+        // Exactly one kind of timer must be set
+        let none = ScheduleRequest {
+            name: "nothing".to_string(),
+            ..Default::default()
+        };
+        assert!(actor_schedule_timer(&none).is_err());
+        let both = ScheduleRequest {
+            name: "both".to_string(),
+            expression: Some("0 0 9 * * * *".to_string()),
+            every: Some("1h".to_string()),
+            ..Default::default()
+        };
+        assert!(actor_schedule_timer(&both).is_err());
+    }
+
+    #[test]
+    fn can_parse_retry_policy() {
+        assert_eq!(
+            cron_retry_policy(&HashMap::new()).unwrap(),
+            RetryPolicy::default()
+        );
+
+        let values = HashMap::from_iter([
+            ("retry_count".to_string(), "3".to_string()),
+            ("retry_backoff".to_string(), "500ms".to_string()),
+            ("invoke_timeout".to_string(), "1m 30s".to_string()),
+        ]);
+        let policy = cron_retry_policy(&values).unwrap();
+        assert_eq!(policy.retry_count, 3);
+        assert_eq!(policy.retry_backoff, Duration::from_millis(500));
+        assert_eq!(policy.invoke_timeout, Some(Duration::from_secs(90)));
+
+        for (key, value) in [
+            ("retry_count", "-1"),
+            ("retry_backoff", "soon"),
+            ("invoke_timeout", "0s"),
+        ] {
+            let values = HashMap::from_iter([(key.to_string(), value.to_string())]);
+            assert!(cron_retry_policy(&values).is_err(), "{key}={value}");
+        }
+    }
 }
@@ -0,0 +1,76 @@
+//! Counters describing what each cron job has done
+//!
+//! The counters are reported in the provider's health check response.
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use tokio::sync::RwLock;
+
+use crate::state::job_key;
+
+/// Counters for a single cron job
+#[derive(Debug, Default)]
+pub(crate) struct JobMetrics {
+    /// Invocations that succeeded, including after retries
+    pub(crate) invocations: AtomicU64,
+    /// Invocations that failed after all their retries
+    pub(crate) failures: AtomicU64,
+    /// Occurrences skipped because a previous invocation was still running
+    pub(crate) skipped: AtomicU64,
+    /// Running invocations aborted to start a new occurrence
+    pub(crate) replaced: AtomicU64,
+}
+
+impl JobMetrics {
+    /// Adds `n` to a counter
+    pub(crate) fn add(counter: &AtomicU64, n: u64) {
+        counter.fetch_add(n, Ordering::Relaxed);
+    }
+}
+
+/// Counters for all cron jobs, keyed by job
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Metrics(Arc<RwLock<BTreeMap<String, Arc<JobMetrics>>>>);
+
+impl Metrics {
+    /// Returns the counters for a job, starting them at zero if it has none
+    pub(crate) async fn job(&self, job: &str) -> Arc<JobMetrics> {
+        self.0
+            .write()
+            .await
+            .entry(job.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Removes the counters of all of an actor's jobs
+    pub(crate) async fn forget_actor(&self, actor_id: &str) {
+        let prefix = job_key(actor_id, "");
+        self.0
+            .write()
+            .await
+            .retain(|job, _| !job.starts_with(&prefix));
+    }
+
+    /// Summarizes the counters of every job, one job per line
+    pub(crate) async fn report(&self) -> String {
+        let mut report = String::new();
+        for (job, metrics) in self.0.read().await.iter() {
+            let _ = writeln!(
+                report,
+                "{job}: invocations={} failures={} skipped={} replaced={}",
+                metrics.invocations.load(Ordering::Relaxed),
+                metrics.failures.load(Ordering::Relaxed),
+                metrics.skipped.load(Ordering::Relaxed),
+                metrics.replaced.load(Ordering::Relaxed),
+            );
+        }
+        report
+    }
+}
@@ -0,0 +1,153 @@
+//! Retries of failed actor invocations
+//!
+//! Failed invocations are retried with jittered exponential backoff. When the retries
+//! run out, the failure is written to a local dead-letter log, so a failed job is
+//! visible after the fact.
+use std::{path::PathBuf, time::Duration};
+
+use rand::Rng;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+
+use crate::state::data_file;
+
+/// Environment variable that overrides the location of the dead-letter log
+const DEAD_LETTER_FILE_ENV: &str = "CRON_DEAD_LETTER_FILE";
+const DEFAULT_DEAD_LETTER_FILE: &str = "wasmcloud-cron-dead-letters.jsonl";
+
+/// Upper limit on the wait between retries, however many attempts have been made
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(300);
+
+/// How a failed invocation is retried
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RetryPolicy {
+    /// Number of retries after the first attempt
+    pub(crate) retry_count: u32,
+    /// Wait before the first retry, doubled for each retry after that
+    pub(crate) retry_backoff: Duration,
+    /// Time allowed for each attempt, if it differs from the rpc default
+    pub(crate) invoke_timeout: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            retry_count: 0,
+            retry_backoff: Duration::from_secs(1),
+            invoke_timeout: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the wait before retry number `retry` (starting at 1). The wait is chosen at
+    /// random from the upper half of the exponential backoff, so jobs that failed together
+    /// don't all retry at the same instant.
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .retry_backoff
+            .checked_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .unwrap_or(MAX_RETRY_BACKOFF)
+            .min(MAX_RETRY_BACKOFF);
+        let half = exponential / 2;
+        half + half.mul_f64(rand::thread_rng().gen::<f64>())
+    }
+}
+
+/// A record of an invocation that failed after all its retries
+#[derive(Debug, Serialize)]
+pub(crate) struct DeadLetter<'a> {
+    pub(crate) actor_id: &'a str,
+    pub(crate) link_name: &'a str,
+    pub(crate) schedule_name: &'a str,
+    pub(crate) expression: &'a str,
+    pub(crate) scheduled_timestamp: u64,
+    pub(crate) sequence: u64,
+    pub(crate) attempts: u32,
+    pub(crate) error: String,
+}
+
+/// Append-only log of invocations that failed after all their retries, one json
+/// record per line
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DeadLetterLog {
+    path: Option<PathBuf>,
+}
+
+impl DeadLetterLog {
+    /// Uses the file named by `CRON_DEAD_LETTER_FILE`, or `wasmcloud-cron-dead-letters.jsonl`
+    /// in the provider's data folder
+    pub(crate) fn from_env() -> Self {
+        let path = data_file(DEAD_LETTER_FILE_ENV, DEFAULT_DEAD_LETTER_FILE);
+        DeadLetterLog { path: Some(path) }
+    }
+
+    /// Appends a record to the log
+    pub(crate) async fn write(&self, record: &DeadLetter<'_>) {
+        tracing::error!(
+            "Giving up on invocation #{} of schedule '{}' for actor {} after {} attempt(s): {}",
+            record.sequence,
+            record.schedule_name,
+            record.actor_id,
+            record.attempts,
+            record.error
+        );
+        let Some(path) = self.path.as_ref() else {
+            return;
+        };
+        let mut line = match serde_json::to_vec(record) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::error!("Error serializing dead letter: {e}");
+                return;
+            }
+        };
+        line.push(b'\n');
+        let result = match tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+        {
+            Ok(mut file) => file.write_all(&line).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::error!("Error writing dead letter to {}: {e}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{RetryPolicy, MAX_RETRY_BACKOFF};
+
+    #[test]
+    fn backoff_is_jittered_and_exponential() {
+        let policy = RetryPolicy {
+            retry_count: 10,
+            retry_backoff: Duration::from_secs(2),
+            invoke_timeout: None,
+        };
+        for retry in 1..=4 {
+            let full = Duration::from_secs(2) * 2u32.pow(retry - 1);
+            for _ in 0..20 {
+                let backoff = policy.backoff(retry);
+                assert!(backoff >= full / 2 && backoff <= full, "{backoff:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let policy = RetryPolicy {
+            retry_count: 100,
+            retry_backoff: Duration::from_secs(2),
+            invoke_timeout: None,
+        };
+        assert!(policy.backoff(100) <= MAX_RETRY_BACKOFF);
+        assert!(policy.backoff(100) >= MAX_RETRY_BACKOFF / 2);
+    }
+}
@@ -0,0 +1,96 @@
+//! Schedules created by actors through the CronScheduler service
+//!
+//! Schedules are kept in a local json file, so they keep running when the provider
+//! restarts and the host links the actors to it again.
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+
+use tokio::sync::RwLock;
+use wasmcloud_interface_cron::ScheduleRequest;
+
+use crate::state::{load_json, save_json};
+
+/// Environment variable that overrides the location of the schedules file
+const SCHEDULES_FILE_ENV: &str = "CRON_SCHEDULES_FILE";
+const DEFAULT_SCHEDULES_FILE: &str = "wasmcloud-cron-schedules.json";
+
+/// Schedules of each actor, by actor ID and then schedule name
+type Schedules = BTreeMap<String, BTreeMap<String, ScheduleRequest>>;
+
+/// Schedules created by actors, mirrored to a local json file
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ScheduleStore {
+    path: Option<PathBuf>,
+    schedules: Arc<RwLock<Schedules>>,
+}
+
+impl ScheduleStore {
+    /// Loads the schedules file named by `CRON_SCHEDULES_FILE`, or
+    /// `wasmcloud-cron-schedules.json` in the provider's data folder
+    /// (see [data_file](crate::state::data_file))
+    pub(crate) fn load() -> Self {
+        let (path, schedules) = load_json(SCHEDULES_FILE_ENV, DEFAULT_SCHEDULES_FILE);
+        ScheduleStore {
+            path: Some(path),
+            schedules: Arc::new(RwLock::new(schedules)),
+        }
+    }
+
+    /// Returns an actor's schedules, sorted by name
+    pub(crate) async fn actor(&self, actor_id: &str) -> Vec<ScheduleRequest> {
+        self.schedules
+            .read()
+            .await
+            .get(actor_id)
+            .map(|schedules| schedules.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Adds a schedule for an actor, replacing any with the same name
+    pub(crate) async fn insert(&self, actor_id: &str, request: ScheduleRequest) {
+        let mut schedules = self.schedules.write().await;
+        schedules
+            .entry(actor_id.to_string())
+            .or_default()
+            .insert(request.name.clone(), request);
+        self.save(&schedules).await;
+    }
+
+    /// Removes an actor's schedule by name, returning true if it existed. If `only` is
+    /// set, the schedule is only removed if it is still the same.
+    pub(crate) async fn remove(
+        &self,
+        actor_id: &str,
+        name: &str,
+        only: Option<&ScheduleRequest>,
+    ) -> bool {
+        let mut schedules = self.schedules.write().await;
+        let Some(actor) = schedules.get_mut(actor_id) else {
+            return false;
+        };
+        if only.is_some() && actor.get(name) != only {
+            return false;
+        }
+        let removed = actor.remove(name).is_some();
+        if actor.is_empty() {
+            schedules.remove(actor_id);
+        }
+        if removed {
+            self.save(&schedules).await;
+        }
+        removed
+    }
+
+    /// Removes all of an actor's schedules
+    pub(crate) async fn forget_actor(&self, actor_id: &str) {
+        let mut schedules = self.schedules.write().await;
+        if schedules.remove(actor_id).is_some() {
+            self.save(&schedules).await;
+        }
+    }
+
+    async fn save(&self, schedules: &Schedules) {
+        if let Some(path) = self.path.as_ref() {
+            save_json(path, schedules).await;
+        }
+    }
+}
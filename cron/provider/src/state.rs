@@ -0,0 +1,212 @@
+//! Persistent record of when each cron job last fired
+//!
+//! The state file lets the provider find occurrences that were missed while it was
+//! down, so they can be handled according to the link's misfire policy, and keeps
+//! invocation sequence numbers increasing across restarts.
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
+
+/// Environment variable that overrides the location of the state file
+const STATE_FILE_ENV: &str = "CRON_STATE_FILE";
+const DEFAULT_STATE_FILE: &str = "wasmcloud-cron-state.json";
+/// Environment variable naming the folder of the provider's files, unless they are
+/// set individually
+const DATA_DIR_ENV: &str = "CRON_DATA_DIR";
+/// Folder of the provider's files under the user's data folder
+const DATA_DIR_NAME: &str = "wasmcloud-cron";
+
+/// Returns the key of an actor's job for a named schedule
+pub(crate) fn job_key(actor_id: &str, schedule_name: &str) -> String {
+    format!("{actor_id}/{schedule_name}")
+}
+
+/// What is remembered about a single cron job
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct JobState {
+    /// The latest occurrence the actor was invoked for
+    pub(crate) last_fired: Option<DateTime<Utc>>,
+    /// Number of invocations so far
+    pub(crate) sequence: u64,
+}
+
+/// State of cron jobs, keyed by job, mirrored to a local json file
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StateStore {
+    path: Option<PathBuf>,
+    jobs: Arc<RwLock<HashMap<String, JobState>>>,
+    /// Number of changes to the jobs, so an older copy isn't saved over a newer one
+    version: Arc<AtomicU64>,
+    /// Version of the jobs in the file, held while writing it
+    saved: Arc<Mutex<u64>>,
+}
+
+impl StateStore {
+    /// Loads the state file named by `CRON_STATE_FILE`, or `wasmcloud-cron-state.json`
+    /// in the provider's data folder (see [data_file]). A missing or unreadable file
+    /// starts with empty state.
+    pub(crate) fn load() -> Self {
+        let (path, jobs) = load_json(STATE_FILE_ENV, DEFAULT_STATE_FILE);
+        StateStore {
+            path: Some(path),
+            jobs: Arc::new(RwLock::new(jobs)),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the state of a job, which is empty if it has never fired
+    pub(crate) async fn get(&self, job: &str) -> JobState {
+        self.jobs.read().await.get(job).copied().unwrap_or_default()
+    }
+
+    /// Changes the state of a job and returns the new state. The change is atomic, so
+    /// invocations of the same job running at once don't overwrite each other's updates.
+    /// It is saved to the file by the next call to [StateStore::update].
+    pub(crate) async fn update_unsaved(
+        &self,
+        job: &str,
+        f: impl FnOnce(&mut JobState),
+    ) -> JobState {
+        let mut jobs = self.jobs.write().await;
+        let state = jobs.entry(job.to_string()).or_default();
+        f(state);
+        *state
+    }
+
+    /// Changes the state of a job like [StateStore::update_unsaved], and saves the state
+    /// of all jobs to the file
+    pub(crate) async fn update(&self, job: &str, f: impl FnOnce(&mut JobState)) -> JobState {
+        let mut jobs = self.jobs.write().await;
+        let state = jobs.entry(job.to_string()).or_default();
+        f(state);
+        let state = *state;
+        self.save(jobs).await;
+        state
+    }
+
+    /// Removes the state of all of an actor's jobs, so a later link starts from scratch
+    pub(crate) async fn forget_actor(&self, actor_id: &str) {
+        let prefix = job_key(actor_id, "");
+        let mut jobs = self.jobs.write().await;
+        let count = jobs.len();
+        jobs.retain(|job, _| !job.starts_with(&prefix));
+        if jobs.len() != count {
+            self.save(jobs).await;
+        }
+    }
+
+    /// Saves a copy of the jobs, writing the file after the lock on them is released
+    async fn save(&self, jobs: tokio::sync::RwLockWriteGuard<'_, HashMap<String, JobState>>) {
+        let Some(path) = self.path.as_ref() else {
+            return;
+        };
+        let version = self.version.fetch_add(1, Ordering::SeqCst) + 1;
+        let copy = jobs.clone();
+        drop(jobs);
+        let mut saved = self.saved.lock().await;
+        // a later change may have been saved while waiting
+        if *saved < version {
+            save_json(path, &copy).await;
+            *saved = version;
+        }
+    }
+}
+
+/// Returns the path of a file named by the environment variable `env`, or `default_file`
+/// in the provider's data folder: `CRON_DATA_DIR`, or `wasmcloud-cron` in
+/// `$XDG_DATA_HOME` or `$HOME/.local/share`, or in the working directory if neither is set.
+/// The data folder is created if it doesn't exist.
+pub(crate) fn data_file(env: &str, default_file: &str) -> PathBuf {
+    if let Ok(path) = std::env::var(env) {
+        return PathBuf::from(path);
+    }
+    let dir = std::env::var_os(DATA_DIR_ENV)
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("XDG_DATA_HOME").map(|d| PathBuf::from(d).join(DATA_DIR_NAME)))
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .map(|d| PathBuf::from(d).join(".local/share").join(DATA_DIR_NAME))
+        })
+        .unwrap_or_else(|| PathBuf::from(DATA_DIR_NAME));
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        tracing::error!("Error creating cron data folder {}: {e}", dir.display());
+    }
+    dir.join(default_file)
+}
+
+/// Reads a json file named by the environment variable `env`, or `default_file` in the
+/// provider's data folder. A missing or unreadable file gives the default value.
+pub(crate) fn load_json<T: DeserializeOwned + Default>(
+    env: &str,
+    default_file: &str,
+) -> (PathBuf, T) {
+    let path = data_file(env, default_file);
+    let value = match std::fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring corrupt cron file {}: {e}", path.display());
+            T::default()
+        }),
+        Err(_) => T::default(),
+    };
+    (path, value)
+}
+
+/// Writes json to a temporary file and renames it over `path`, so a crash while saving
+/// can't leave a truncated file behind
+pub(crate) async fn save_json<T: Serialize>(path: &Path, value: &T) {
+    let tmp = path.with_extension("tmp");
+    let result = match serde_json::to_vec(value) {
+        Ok(bytes) => match tokio::fs::write(&tmp, bytes).await {
+            Ok(()) => tokio::fs::rename(&tmp, path).await,
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = result {
+        tracing::error!("Error saving cron state to {}: {e}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn saves_once_per_update() {
+        let path =
+            std::env::temp_dir().join(format!("cron-state-test-{}.json", rand::random::<u32>()));
+        let store = StateStore {
+            path: Some(path.clone()),
+            ..Default::default()
+        };
+        let state = store
+            .update_unsaved("a/default", |job| job.sequence += 1)
+            .await;
+        assert_eq!(state.sequence, 1);
+        assert!(!path.exists(), "unsaved update wrote the file");
+
+        let fired = Utc::now();
+        store
+            .update("a/default", |job| job.last_fired = Some(fired))
+            .await;
+        let saved: HashMap<String, JobState> =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(
+            saved.get("a/default"),
+            Some(&JobState {
+                last_fired: Some(fired),
+                sequence: 1,
+            })
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,225 @@
+//! Timing of cron job occurrences
+//!
+//! The wait for an occurrence is measured against a monotonic deadline with full
+//! `chrono::Duration` precision, then checked against the wall clock, so an occurrence
+//! never fires before its scheduled instant and is never fired twice.
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+use crate::timer::Timer;
+
+/// Source of wall clock time, which can be replaced in tests
+pub(crate) trait Clock: Send + Sync + 'static {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system's wall clock
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// The next thing a cron job should do
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Tick {
+    /// Occurrences that passed without being fired, oldest first
+    Missed(Vec<DateTime<Tz>>),
+    /// An occurrence whose scheduled instant has been reached
+    Due(DateTime<Tz>),
+    /// The schedule has no more occurrences
+    Finished,
+}
+
+/// Steps through the occurrences of a timer, waiting for each one to come due
+pub(crate) struct Ticker<C> {
+    timer: Timer,
+    timezone: Tz,
+    clock: C,
+    /// The latest occurrence returned, or the time the ticker started from
+    last: DateTime<Tz>,
+}
+
+impl<C: Clock> Ticker<C> {
+    /// Creates a ticker for occurrences of `timer` after `start`
+    pub(crate) fn new(timer: Timer, timezone: Tz, clock: C, start: DateTime<Utc>) -> Self {
+        Ticker {
+            timer,
+            timezone,
+            clock,
+            last: start.with_timezone(&timezone),
+        }
+    }
+
+    /// Returns any occurrences that have already passed, otherwise waits for the next
+    /// occurrence to come due. Each occurrence is returned exactly once.
+    pub(crate) async fn tick(&mut self) -> Tick {
+        let now = self.clock.now().with_timezone(&self.timezone);
+        let missed: Vec<_> = self
+            .timer
+            .after(&self.last)
+            .take_while(|t| *t <= now)
+            .collect();
+        if let Some(latest) = missed.last() {
+            self.last = *latest;
+            return Tick::Missed(missed);
+        }
+
+        let next = if let Some(next) = self.timer.after(&self.last).next() {
+            next
+        } else {
+            return Tick::Finished;
+        };
+        wait_until(&self.clock, next.with_timezone(&Utc)).await;
+        self.last = next;
+        Tick::Due(next)
+    }
+}
+
+/// Waits until the wall clock reaches `instant`, never returning before it.
+///
+/// Each wait sleeps until a monotonic deadline, so it isn't affected by wall clock
+/// adjustments while sleeping. If the wall clock is still short of `instant` on waking
+/// (because it was set back, or drifted from the monotonic clock), it waits again for
+/// the remainder.
+pub(crate) async fn wait_until<C: Clock>(clock: &C, instant: DateTime<Utc>) {
+    // `to_std` fails for negative durations, when the instant has already passed
+    while let Ok(remaining) = (instant - clock.now()).to_std() {
+        if remaining.is_zero() {
+            break;
+        }
+        tokio::time::sleep_until(tokio::time::Instant::now() + remaining).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        str::FromStr,
+        sync::{Arc, Mutex},
+    };
+
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use chrono_tz::Tz;
+    use cron::Schedule;
+
+    use super::{wait_until, Clock, Tick, Ticker};
+
+    /// A wall clock that follows tokio's (pausable) clock, and can be moved independently
+    #[derive(Clone)]
+    struct MockClock {
+        start: tokio::time::Instant,
+        wall_start: DateTime<Utc>,
+        offset: Arc<Mutex<Duration>>,
+    }
+
+    impl MockClock {
+        fn new(wall_start: DateTime<Utc>) -> Self {
+            MockClock {
+                start: tokio::time::Instant::now(),
+                wall_start,
+                offset: Arc::new(Mutex::new(Duration::zero())),
+            }
+        }
+
+        /// Moves the wall clock without moving the monotonic clock
+        fn adjust(&self, by: Duration) {
+            let mut offset = self.offset.lock().unwrap();
+            *offset = offset.checked_add(&by).unwrap();
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Utc> {
+            let elapsed = Duration::from_std(self.start.elapsed()).unwrap();
+            self.wall_start + elapsed + *self.offset.lock().unwrap()
+        }
+    }
+
+    fn every_second() -> Schedule {
+        Schedule::from_str("* * * * * * *").unwrap()
+    }
+
+    fn start_time() -> DateTime<Utc> {
+        // Start part way through a second, so waits aren't whole seconds
+        Utc.with_ymd_and_hms(2023, 1, 1, 12, 0, 0).unwrap() + Duration::milliseconds(300)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fires_each_occurrence_once_on_time() {
+        let clock = MockClock::new(start_time());
+        let mut ticker = Ticker::new(every_second().into(), Tz::UTC, clock.clone(), start_time());
+
+        let mut previous = None;
+        for _ in 0..5 {
+            let due = match ticker.tick().await {
+                Tick::Due(due) => due,
+                tick => panic!("expected due occurrence, got {tick:?}"),
+            };
+            // Fires exactly at the scheduled instant, not before or a whole second late
+            assert_eq!(clock.now(), due);
+            if let Some(previous) = previous {
+                assert_eq!(due - previous, Duration::seconds(1));
+            }
+            previous = Some(due);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reports_occurrences_missed_by_slow_invocations() {
+        let clock = MockClock::new(start_time());
+        let mut ticker = Ticker::new(every_second().into(), Tz::UTC, clock.clone(), start_time());
+
+        let first = ticker.tick().await;
+        assert!(matches!(first, Tick::Due(_)));
+
+        // An invocation taking 2.5 seconds misses two occurrences
+        tokio::time::sleep(std::time::Duration::from_millis(2500)).await;
+        match ticker.tick().await {
+            Tick::Missed(missed) => assert_eq!(missed.len(), 2),
+            tick => panic!("expected missed occurrences, got {tick:?}"),
+        }
+
+        // The next occurrence is half a second away, and only comes due once
+        let before = clock.now();
+        let Tick::Due(due) = ticker.tick().await else {
+            panic!("expected due occurrence")
+        };
+        assert_eq!(
+            due.with_timezone(&Utc) - before,
+            Duration::milliseconds(500)
+        );
+        assert!(matches!(ticker.tick().await, Tick::Due(later) if later > due));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_never_returns_early_when_wall_clock_goes_back() {
+        let clock = MockClock::new(start_time());
+        let target = start_time() + Duration::seconds(10);
+
+        let waiter = {
+            let clock = clock.clone();
+            tokio::spawn(async move {
+                wait_until(&clock, target).await;
+                clock.now()
+            })
+        };
+        // Set the wall clock back a minute while the waiter sleeps
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        clock.adjust(Duration::seconds(-60));
+
+        let woke_at = waiter.await.unwrap();
+        assert_eq!(woke_at, target);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn finishes_when_schedule_has_no_more_occurrences() {
+        let clock = MockClock::new(start_time());
+        let schedule = Schedule::from_str("0 0 0 1 1 * 2022").unwrap();
+        let mut ticker = Ticker::new(schedule.into(), Tz::UTC, clock, start_time());
+        assert_eq!(ticker.tick().await, Tick::Finished);
+    }
+}
@@ -0,0 +1,142 @@
+//! The kinds of schedule a cron job can follow
+//!
+//! Besides cron expressions, a job can fire once at a fixed instant, or repeatedly at a
+//! fixed interval. These can't be expressed as cron expressions, but produce occurrences
+//! the same way, so they share the same ticker and invocation path.
+use std::{fmt, time::Duration};
+
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use cron::Schedule;
+
+/// When a cron job fires
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Timer {
+    /// Every occurrence of a cron expression
+    Cron(Box<Schedule>),
+    /// Once, at a fixed instant
+    At(DateTime<Utc>),
+    /// At `start_at`, then every `interval` after it
+    Every {
+        interval: Duration,
+        start_at: DateTime<Utc>,
+    },
+}
+
+impl From<Schedule> for Timer {
+    fn from(schedule: Schedule) -> Self {
+        Timer::Cron(Box::new(schedule))
+    }
+}
+
+impl Timer {
+    /// Returns the occurrences strictly after `after`, in order, in the time zone of `after`
+    pub(crate) fn after<'a, Z: TimeZone + 'a>(
+        &'a self,
+        after: &DateTime<Z>,
+    ) -> Box<dyn Iterator<Item = DateTime<Z>> + 'a> {
+        let timezone = after.timezone();
+        match self {
+            Timer::Cron(schedule) => Box::new(schedule.after(after)),
+            Timer::At(at) => Box::new(
+                Some(at.with_timezone(&timezone))
+                    .filter(|at| at > after)
+                    .into_iter(),
+            ),
+            Timer::Every { interval, start_at } => {
+                let interval = chrono::Duration::from_std(*interval)
+                    .expect("interval is checked when the timer is parsed");
+                let first = first_interval_after(*start_at, interval, after.with_timezone(&Utc));
+                Box::new(
+                    std::iter::successors(first, move |t| t.checked_add_signed(interval))
+                        .map(move |t| t.with_timezone(&timezone)),
+                )
+            }
+        }
+    }
+}
+
+/// Returns the first of `start`, `start + interval`, `start + 2 * interval`, ... that is
+/// after `after`, or None if it is out of range
+fn first_interval_after(
+    start: DateTime<Utc>,
+    interval: chrono::Duration,
+    after: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let elapsed = match (after - start).num_nanoseconds() {
+        Some(elapsed) if elapsed >= 0 => elapsed,
+        // `after` is before the start
+        Some(_) => return Some(start),
+        None if after < start => return Some(start),
+        None => return None,
+    };
+    let interval_nanos = interval.num_nanoseconds()?;
+    let intervals = elapsed / interval_nanos + 1;
+    start.checked_add_signed(chrono::Duration::nanoseconds(
+        intervals.checked_mul(interval_nanos)?,
+    ))
+}
+
+impl fmt::Display for Timer {
+    /// Formats the timer the way it is written in link values
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timer::Cron(schedule) => write!(f, "{schedule}"),
+            Timer::At(at) => write!(f, "at={}", at.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+            Timer::Every { interval, start_at } => write!(
+                f,
+                "every={} start_at={}",
+                humantime::format_duration(*interval),
+                start_at.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+
+    use super::Timer;
+
+    #[test]
+    fn at_fires_once_if_not_passed() {
+        let at = Utc.with_ymd_and_hms(2023, 6, 1, 9, 0, 0).unwrap();
+        let timer = Timer::At(at);
+        let before = at - chrono::Duration::seconds(1);
+        assert_eq!(timer.after(&before).collect::<Vec<_>>(), [at]);
+        assert_eq!(timer.after(&at).count(), 0);
+    }
+
+    #[test]
+    fn every_keeps_phase_of_start() {
+        let start_at = Utc.with_ymd_and_hms(2023, 6, 1, 9, 0, 0).unwrap();
+        let timer = Timer::Every {
+            interval: Duration::from_secs(90),
+            start_at,
+        };
+
+        // Before the start, the first occurrence is the start itself
+        let before = start_at - chrono::Duration::hours(1);
+        assert_eq!(timer.after(&before).next(), Some(start_at));
+
+        // Later occurrences stay on the 90 second grid from the start
+        let later = start_at + chrono::Duration::seconds(200);
+        let upcoming: Vec<_> = timer.after(&later).take(2).collect();
+        assert_eq!(
+            upcoming,
+            [
+                start_at + chrono::Duration::seconds(270),
+                start_at + chrono::Duration::seconds(360)
+            ]
+        );
+
+        // An occurrence exactly at `after` isn't included
+        let on_grid = start_at + chrono::Duration::seconds(180);
+        assert_eq!(
+            timer.after(&on_grid).next(),
+            Some(start_at + chrono::Duration::seconds(270))
+        );
+    }
+}
@@ -28,6 +28,11 @@ pub struct AuthorizePaymentRequest {
     /// Amount of transaction, in cents.
     #[serde(default)]
     pub amount: u32,
+    /// Optional key that identifies retries: a repeated request with the
+    /// same key returns the result of the first one
+    #[serde(rename = "idempotencyKey")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
     /// The entity (customer) requesting this payment
     #[serde(rename = "paymentEntity")]
     #[serde(default)]
@@ -55,9 +60,15 @@ pub fn encode_authorize_payment_request<W: wasmbus_rpc::cbor::Write>(
 where
     <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
 {
-    e.map(5)?;
+    e.map(6)?;
     e.str("amount")?;
     e.u32(val.amount)?;
+    if let Some(val) = val.idempotency_key.as_ref() {
+        e.str("idempotencyKey")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
     e.str("paymentEntity")?;
     e.str(&val.payment_entity)?;
     e.str("paymentMethod")?;
@@ -76,6 +87,7 @@ pub fn decode_authorize_payment_request(
 ) -> Result<AuthorizePaymentRequest, RpcError> {
     let __result = {
         let mut amount: Option<u32> = None;
+        let mut idempotency_key: Option<Option<String>> = Some(None);
         let mut payment_entity: Option<String> = None;
         let mut payment_method: Option<String> = None;
         let mut reference_id: Option<String> = None;
@@ -95,10 +107,18 @@ pub fn decode_authorize_payment_request(
             for __i in 0..(len as usize) {
                 match __i {
                     0 => amount = Some(d.u32()?),
-                    1 => payment_entity = Some(d.str()?.to_string()),
-                    2 => payment_method = Some(d.str()?.to_string()),
-                    3 => reference_id = Some(d.str()?.to_string()),
-                    4 => tax = Some(d.u32()?),
+                    1 => {
+                        idempotency_key = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    2 => payment_entity = Some(d.str()?.to_string()),
+                    3 => payment_method = Some(d.str()?.to_string()),
+                    4 => reference_id = Some(d.str()?.to_string()),
+                    5 => tax = Some(d.u32()?),
                     _ => d.skip()?,
                 }
             }
@@ -107,6 +127,14 @@ pub fn decode_authorize_payment_request(
             for __i in 0..(len as usize) {
                 match d.str()? {
                     "amount" => amount = Some(d.u32()?),
+                    "idempotencyKey" => {
+                        idempotency_key = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
                     "paymentEntity" => payment_entity = Some(d.str()?.to_string()),
                     "paymentMethod" => payment_method = Some(d.str()?.to_string()),
                     "referenceId" => reference_id = Some(d.str()?.to_string()),
@@ -123,12 +151,13 @@ pub fn decode_authorize_payment_request(
                     "missing field AuthorizePaymentRequest.amount (#0)".to_string(),
                 ));
             },
+            idempotency_key: idempotency_key.unwrap(),
 
             payment_entity: if let Some(__x) = payment_entity {
                 __x
             } else {
                 return Err(RpcError::Deser(
-                    "missing field AuthorizePaymentRequest.payment_entity (#1)".to_string(),
+                    "missing field AuthorizePaymentRequest.payment_entity (#2)".to_string(),
                 ));
             },
 
@@ -136,7 +165,7 @@ pub fn decode_authorize_payment_request(
                 __x
             } else {
                 return Err(RpcError::Deser(
-                    "missing field AuthorizePaymentRequest.payment_method (#2)".to_string(),
+                    "missing field AuthorizePaymentRequest.payment_method (#3)".to_string(),
                 ));
             },
 
@@ -144,7 +173,7 @@ pub fn decode_authorize_payment_request(
                 __x
             } else {
                 return Err(RpcError::Deser(
-                    "missing field AuthorizePaymentRequest.reference_id (#3)".to_string(),
+                    "missing field AuthorizePaymentRequest.reference_id (#4)".to_string(),
                 ));
             },
 
@@ -152,7 +181,7 @@ pub fn decode_authorize_payment_request(
                 __x
             } else {
                 return Err(RpcError::Deser(
-                    "missing field AuthorizePaymentRequest.tax (#4)".to_string(),
+                    "missing field AuthorizePaymentRequest.tax (#5)".to_string(),
                 ));
             },
         }
@@ -290,6 +319,10 @@ pub fn decode_authorize_payment_response(
 /// to be withdrawn from the payer's account)
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CompletePaymentRequest {
+    /// Amount to capture, in cents, including tax. If not set, the full
+    /// authorized amount and tax are captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<u32>,
     /// authorization code from the AuthorizePaymentResponse
     #[serde(rename = "authCode")]
     #[serde(default)]
@@ -298,6 +331,11 @@ pub struct CompletePaymentRequest {
     /// (e.g., memo field of a credit card statement) |
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Optional key that identifies retries: a repeated request with the
+    /// same key returns the result of the first one
+    #[serde(rename = "idempotencyKey")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
 }
 
 // Encode CompletePaymentRequest as CBOR and append to output stream
@@ -310,7 +348,13 @@ pub fn encode_complete_payment_request<W: wasmbus_rpc::cbor::Write>(
 where
     <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
 {
-    e.map(2)?;
+    e.map(4)?;
+    if let Some(val) = val.amount.as_ref() {
+        e.str("amount")?;
+        e.u32(*val)?;
+    } else {
+        e.null()?;
+    }
     e.str("authCode")?;
     e.str(&val.auth_code)?;
     if let Some(val) = val.description.as_ref() {
@@ -319,6 +363,12 @@ where
     } else {
         e.null()?;
     }
+    if let Some(val) = val.idempotency_key.as_ref() {
+        e.str("idempotencyKey")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
     Ok(())
 }
 
@@ -328,8 +378,10 @@ pub fn decode_complete_payment_request(
     d: &mut wasmbus_rpc::cbor::Decoder<'_>,
 ) -> Result<CompletePaymentRequest, RpcError> {
     let __result = {
+        let mut amount: Option<Option<u32>> = Some(None);
         let mut auth_code: Option<String> = None;
         let mut description: Option<Option<String>> = Some(None);
+        let mut idempotency_key: Option<Option<String>> = Some(None);
 
         let is_array = match d.datatype()? {
             wasmbus_rpc::cbor::Type::Array => true,
@@ -344,8 +396,16 @@ pub fn decode_complete_payment_request(
             let len = d.fixed_array()?;
             for __i in 0..(len as usize) {
                 match __i {
-                    0 => auth_code = Some(d.str()?.to_string()),
-                    1 => {
+                    0 => {
+                        amount = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u32()?))
+                        }
+                    }
+                    1 => auth_code = Some(d.str()?.to_string()),
+                    2 => {
                         description = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
                             d.skip()?;
                             Some(None)
@@ -353,6 +413,14 @@ pub fn decode_complete_payment_request(
                             Some(Some(d.str()?.to_string()))
                         }
                     }
+                    3 => {
+                        idempotency_key = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
 
                     _ => d.skip()?,
                 }
@@ -361,6 +429,14 @@ pub fn decode_complete_payment_request(
             let len = d.fixed_map()?;
             for __i in 0..(len as usize) {
                 match d.str()? {
+                    "amount" => {
+                        amount = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u32()?))
+                        }
+                    }
                     "authCode" => auth_code = Some(d.str()?.to_string()),
                     "description" => {
                         description = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
@@ -370,25 +446,39 @@ pub fn decode_complete_payment_request(
                             Some(Some(d.str()?.to_string()))
                         }
                     }
+                    "idempotencyKey" => {
+                        idempotency_key = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
                     _ => d.skip()?,
                 }
             }
         }
         CompletePaymentRequest {
+            amount: amount.unwrap(),
+
             auth_code: if let Some(__x) = auth_code {
                 __x
             } else {
                 return Err(RpcError::Deser(
-                    "missing field CompletePaymentRequest.auth_code (#0)".to_string(),
+                    "missing field CompletePaymentRequest.auth_code (#1)".to_string(),
                 ));
             },
             description: description.unwrap(),
+            idempotency_key: idempotency_key.unwrap(),
         }
     };
     Ok(__result)
 }
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CompletePaymentResponse {
+    /// Amount captured, in cents, including tax
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<u32>,
     /// True if the payment was successful
     #[serde(default)]
     pub success: bool,
@@ -410,7 +500,13 @@ pub fn encode_complete_payment_response<W: wasmbus_rpc::cbor::Write>(
 where
     <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
 {
-    e.map(3)?;
+    e.map(4)?;
+    if let Some(val) = val.amount.as_ref() {
+        e.str("amount")?;
+        e.u32(*val)?;
+    } else {
+        e.null()?;
+    }
     e.str("success")?;
     e.bool(val.success)?;
     e.str("timestamp")?;
@@ -426,6 +522,7 @@ pub fn decode_complete_payment_response(
     d: &mut wasmbus_rpc::cbor::Decoder<'_>,
 ) -> Result<CompletePaymentResponse, RpcError> {
     let __result = {
+        let mut amount: Option<Option<u32>> = Some(None);
         let mut success: Option<bool> = None;
         let mut timestamp: Option<u64> = None;
         let mut txid: Option<String> = None;
@@ -443,9 +540,17 @@ pub fn decode_complete_payment_response(
             let len = d.fixed_array()?;
             for __i in 0..(len as usize) {
                 match __i {
-                    0 => success = Some(d.bool()?),
-                    1 => timestamp = Some(d.u64()?),
-                    2 => txid = Some(d.str()?.to_string()),
+                    0 => {
+                        amount = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u32()?))
+                        }
+                    }
+                    1 => success = Some(d.bool()?),
+                    2 => timestamp = Some(d.u64()?),
+                    3 => txid = Some(d.str()?.to_string()),
                     _ => d.skip()?,
                 }
             }
@@ -453,6 +558,14 @@ pub fn decode_complete_payment_response(
             let len = d.fixed_map()?;
             for __i in 0..(len as usize) {
                 match d.str()? {
+                    "amount" => {
+                        amount = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u32()?))
+                        }
+                    }
                     "success" => success = Some(d.bool()?),
                     "timestamp" => timestamp = Some(d.u64()?),
                     "txid" => txid = Some(d.str()?.to_string()),
@@ -461,11 +574,13 @@ pub fn decode_complete_payment_response(
             }
         }
         CompletePaymentResponse {
+            amount: amount.unwrap(),
+
             success: if let Some(__x) = success {
                 __x
             } else {
                 return Err(RpcError::Deser(
-                    "missing field CompletePaymentResponse.success (#0)".to_string(),
+                    "missing field CompletePaymentResponse.success (#1)".to_string(),
                 ));
             },
 
@@ -473,7 +588,7 @@ pub fn decode_complete_payment_response(
                 __x
             } else {
                 return Err(RpcError::Deser(
-                    "missing field CompletePaymentResponse.timestamp (#1)".to_string(),
+                    "missing field CompletePaymentResponse.timestamp (#2)".to_string(),
                 ));
             },
 
@@ -481,63 +596,215 @@ pub fn decode_complete_payment_response(
                 __x
             } else {
                 return Err(RpcError::Deser(
-                    "missing field CompletePaymentResponse.txid (#2)".to_string(),
+                    "missing field CompletePaymentResponse.txid (#3)".to_string(),
                 ));
             },
         }
     };
     Ok(__result)
 }
-/// A PaymentMethod contains a token string and a description
+/// Response to GetTransaction
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
-pub struct PaymentMethod {
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
+pub struct GetTransactionResponse {
+    /// True if the transaction exists
+    #[serde(default)]
+    pub found: bool,
+    /// The transaction, if found
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub token: Option<String>,
+    pub transaction: Option<Transaction>,
 }
 
-// Encode PaymentMethod as CBOR and append to output stream
+// Encode GetTransactionResponse as CBOR and append to output stream
 #[doc(hidden)]
 #[allow(unused_mut)]
-pub fn encode_payment_method<W: wasmbus_rpc::cbor::Write>(
+pub fn encode_get_transaction_response<W: wasmbus_rpc::cbor::Write>(
     mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
-    val: &PaymentMethod,
+    val: &GetTransactionResponse,
 ) -> RpcResult<()>
 where
     <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
 {
     e.map(2)?;
-    if let Some(val) = val.description.as_ref() {
-        e.str("description")?;
+    e.str("found")?;
+    e.bool(val.found)?;
+    if let Some(val) = val.transaction.as_ref() {
+        e.str("transaction")?;
+        encode_transaction(e, val)?;
+    } else {
+        e.null()?;
+    }
+    Ok(())
+}
+
+// Decode GetTransactionResponse from cbor input stream
+#[doc(hidden)]
+pub fn decode_get_transaction_response(
+    d: &mut wasmbus_rpc::cbor::Decoder<'_>,
+) -> Result<GetTransactionResponse, RpcError> {
+    let __result = {
+        let mut found: Option<bool> = None;
+        let mut transaction: Option<Option<Transaction>> = Some(None);
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct GetTransactionResponse, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => found = Some(d.bool()?),
+                    1 => {
+                        transaction = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(decode_transaction(d).map_err(|e| {
+                                format!(
+                                    "decoding 'org.wasmcloud.examples.payments#Transaction': {}",
+                                    e
+                                )
+                            })?))
+                        }
+                    }
+
+                    _ => d.skip()?,
+                }
+            }
+        } else {
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "found" => found = Some(d.bool()?),
+                    "transaction" => {
+                        transaction = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(decode_transaction(d).map_err(|e| {
+                                format!(
+                                    "decoding 'org.wasmcloud.examples.payments#Transaction': {}",
+                                    e
+                                )
+                            })?))
+                        }
+                    }
+                    _ => d.skip()?,
+                }
+            }
+        }
+        GetTransactionResponse {
+            found: if let Some(__x) = found {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field GetTransactionResponse.found (#0)".to_string(),
+                ));
+            },
+            transaction: transaction.unwrap(),
+        }
+    };
+    Ok(__result)
+}
+/// Parameters sent for ListTransactions. All filters are optional.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ListTransactionsRequest {
+    /// Maximum number of transactions to return (default 100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Only transactions of this payment entity
+    #[serde(rename = "paymentEntity")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payment_entity: Option<String>,
+    /// Only transactions with this reference id
+    #[serde(rename = "referenceId")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_id: Option<String>,
+    /// Only transactions completed at or after this time (milliseconds since epoch, UTC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<u64>,
+    /// `nextStart` from the previous response, to continue a listing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    /// Only transactions completed before this time (milliseconds since epoch, UTC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<u64>,
+}
+
+// Encode ListTransactionsRequest as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_list_transactions_request<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &ListTransactionsRequest,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.map(6)?;
+    if let Some(val) = val.limit.as_ref() {
+        e.str("limit")?;
+        e.u32(*val)?;
+    } else {
+        e.null()?;
+    }
+    if let Some(val) = val.payment_entity.as_ref() {
+        e.str("paymentEntity")?;
         e.str(val)?;
     } else {
         e.null()?;
     }
-    if let Some(val) = val.token.as_ref() {
-        e.str("token")?;
+    if let Some(val) = val.reference_id.as_ref() {
+        e.str("referenceId")?;
         e.str(val)?;
     } else {
         e.null()?;
     }
+    if let Some(val) = val.since.as_ref() {
+        e.str("since")?;
+        e.u64(*val)?;
+    } else {
+        e.null()?;
+    }
+    if let Some(val) = val.start.as_ref() {
+        e.str("start")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    if let Some(val) = val.until.as_ref() {
+        e.str("until")?;
+        e.u64(*val)?;
+    } else {
+        e.null()?;
+    }
     Ok(())
 }
 
-// Decode PaymentMethod from cbor input stream
+// Decode ListTransactionsRequest from cbor input stream
 #[doc(hidden)]
-pub fn decode_payment_method(
+pub fn decode_list_transactions_request(
     d: &mut wasmbus_rpc::cbor::Decoder<'_>,
-) -> Result<PaymentMethod, RpcError> {
+) -> Result<ListTransactionsRequest, RpcError> {
     let __result = {
-        let mut description: Option<Option<String>> = Some(None);
-        let mut token: Option<Option<String>> = Some(None);
+        let mut limit: Option<Option<u32>> = Some(None);
+        let mut payment_entity: Option<Option<String>> = Some(None);
+        let mut reference_id: Option<Option<String>> = Some(None);
+        let mut since: Option<Option<u64>> = Some(None);
+        let mut start: Option<Option<String>> = Some(None);
+        let mut until: Option<Option<u64>> = Some(None);
 
         let is_array = match d.datatype()? {
             wasmbus_rpc::cbor::Type::Array => true,
             wasmbus_rpc::cbor::Type::Map => false,
             _ => {
                 return Err(RpcError::Deser(
-                    "decoding struct PaymentMethod, expected array or map".to_string(),
+                    "decoding struct ListTransactionsRequest, expected array or map".to_string(),
                 ))
             }
         };
@@ -546,21 +813,53 @@ pub fn decode_payment_method(
             for __i in 0..(len as usize) {
                 match __i {
                     0 => {
-                        description = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                        limit = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
                             d.skip()?;
                             Some(None)
                         } else {
-                            Some(Some(d.str()?.to_string()))
+                            Some(Some(d.u32()?))
                         }
                     }
                     1 => {
-                        token = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                        payment_entity = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    2 => {
+                        reference_id = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    3 => {
+                        since = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u64()?))
+                        }
+                    }
+                    4 => {
+                        start = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
                             d.skip()?;
                             Some(None)
                         } else {
                             Some(Some(d.str()?.to_string()))
                         }
                     }
+                    5 => {
+                        until = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u64()?))
+                        }
+                    }
 
                     _ => d.skip()?,
                 }
@@ -569,86 +868,1344 @@ pub fn decode_payment_method(
             let len = d.fixed_map()?;
             for __i in 0..(len as usize) {
                 match d.str()? {
-                    "description" => {
-                        description = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                    "limit" => {
+                        limit = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u32()?))
+                        }
+                    }
+                    "paymentEntity" => {
+                        payment_entity = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
                             d.skip()?;
                             Some(None)
                         } else {
                             Some(Some(d.str()?.to_string()))
                         }
                     }
-                    "token" => {
-                        token = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                    "referenceId" => {
+                        reference_id = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "since" => {
+                        since = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u64()?))
+                        }
+                    }
+                    "start" => {
+                        start = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
                             d.skip()?;
                             Some(None)
                         } else {
                             Some(Some(d.str()?.to_string()))
                         }
                     }
+                    "until" => {
+                        until = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u64()?))
+                        }
+                    }
                     _ => d.skip()?,
                 }
             }
         }
-        PaymentMethod {
-            description: description.unwrap(),
-            token: token.unwrap(),
+        ListTransactionsRequest {
+            limit: limit.unwrap(),
+            payment_entity: payment_entity.unwrap(),
+            reference_id: reference_id.unwrap(),
+            since: since.unwrap(),
+            start: start.unwrap(),
+            until: until.unwrap(),
         }
     };
     Ok(__result)
 }
-/// An ordered list of payment methods.
-pub type PaymentMethods = Vec<PaymentMethod>;
+/// Response to ListTransactions
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ListTransactionsResponse {
+    /// Set if there are more transactions; pass as `start` to get them
+    #[serde(rename = "nextStart")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_start: Option<String>,
+    /// Matching transactions, oldest first
+    pub transactions: Transactions,
+}
 
-// Encode PaymentMethods as CBOR and append to output stream
+// Encode ListTransactionsResponse as CBOR and append to output stream
 #[doc(hidden)]
 #[allow(unused_mut)]
-pub fn encode_payment_methods<W: wasmbus_rpc::cbor::Write>(
+pub fn encode_list_transactions_response<W: wasmbus_rpc::cbor::Write>(
     mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
-    val: &PaymentMethods,
+    val: &ListTransactionsResponse,
 ) -> RpcResult<()>
 where
     <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
 {
-    e.array(val.len() as u64)?;
-    for item in val.iter() {
-        encode_payment_method(e, item)?;
+    e.map(2)?;
+    if let Some(val) = val.next_start.as_ref() {
+        e.str("nextStart")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
     }
+    e.str("transactions")?;
+    encode_transactions(e, &val.transactions)?;
     Ok(())
 }
 
-// Decode PaymentMethods from cbor input stream
+// Decode ListTransactionsResponse from cbor input stream
 #[doc(hidden)]
-pub fn decode_payment_methods(
+pub fn decode_list_transactions_response(
     d: &mut wasmbus_rpc::cbor::Decoder<'_>,
-) -> Result<PaymentMethods, RpcError> {
+) -> Result<ListTransactionsResponse, RpcError> {
     let __result = {
-        if let Some(n) = d.array()? {
-            let mut arr: Vec<PaymentMethod> = Vec::with_capacity(n as usize);
-            for _ in 0..(n as usize) {
-                arr.push(decode_payment_method(d).map_err(|e| {
-                    format!(
-                        "decoding 'org.wasmcloud.examples.payments#PaymentMethod': {}",
-                        e
-                    )
-                })?)
+        let mut next_start: Option<Option<String>> = Some(None);
+        let mut transactions: Option<Transactions> = None;
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct ListTransactionsResponse, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => {
+                        next_start = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    1 => {
+                        transactions = Some(decode_transactions(d).map_err(|e| {
+                            format!(
+                                "decoding 'org.wasmcloud.examples.payments#Transactions': {}",
+                                e
+                            )
+                        })?)
+                    }
+                    _ => d.skip()?,
+                }
+            }
+        } else {
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "nextStart" => {
+                        next_start = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "transactions" => {
+                        transactions = Some(decode_transactions(d).map_err(|e| {
+                            format!(
+                                "decoding 'org.wasmcloud.examples.payments#Transactions': {}",
+                                e
+                            )
+                        })?)
+                    }
+                    _ => d.skip()?,
+                }
+            }
+        }
+        ListTransactionsResponse {
+            next_start: next_start.unwrap(),
+
+            transactions: if let Some(__x) = transactions {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field ListTransactionsResponse.transactions (#1)".to_string(),
+                ));
+            },
+        }
+    };
+    Ok(__result)
+}
+/// A PaymentMethod contains a token string and a description
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PaymentMethod {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+// Encode PaymentMethod as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_payment_method<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &PaymentMethod,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.map(2)?;
+    if let Some(val) = val.description.as_ref() {
+        e.str("description")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    if let Some(val) = val.token.as_ref() {
+        e.str("token")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    Ok(())
+}
+
+// Decode PaymentMethod from cbor input stream
+#[doc(hidden)]
+pub fn decode_payment_method(
+    d: &mut wasmbus_rpc::cbor::Decoder<'_>,
+) -> Result<PaymentMethod, RpcError> {
+    let __result = {
+        let mut description: Option<Option<String>> = Some(None);
+        let mut token: Option<Option<String>> = Some(None);
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct PaymentMethod, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => {
+                        description = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    1 => {
+                        token = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+
+                    _ => d.skip()?,
+                }
+            }
+        } else {
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "description" => {
+                        description = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "token" => {
+                        token = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    _ => d.skip()?,
+                }
+            }
+        }
+        PaymentMethod {
+            description: description.unwrap(),
+            token: token.unwrap(),
+        }
+    };
+    Ok(__result)
+}
+/// An ordered list of payment methods.
+pub type PaymentMethods = Vec<PaymentMethod>;
+
+// Encode PaymentMethods as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_payment_methods<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &PaymentMethods,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.array(val.len() as u64)?;
+    for item in val.iter() {
+        encode_payment_method(e, item)?;
+    }
+    Ok(())
+}
+
+// Decode PaymentMethods from cbor input stream
+#[doc(hidden)]
+pub fn decode_payment_methods(
+    d: &mut wasmbus_rpc::cbor::Decoder<'_>,
+) -> Result<PaymentMethods, RpcError> {
+    let __result = {
+        if let Some(n) = d.array()? {
+            let mut arr: Vec<PaymentMethod> = Vec::with_capacity(n as usize);
+            for _ in 0..(n as usize) {
+                arr.push(decode_payment_method(d).map_err(|e| {
+                    format!(
+                        "decoding 'org.wasmcloud.examples.payments#PaymentMethod': {}",
+                        e
+                    )
+                })?)
+            }
+            arr
+        } else {
+            // indefinite array
+            let mut arr: Vec<PaymentMethod> = Vec::new();
+            loop {
+                match d.datatype() {
+                    Err(_) => break,
+                    Ok(wasmbus_rpc::cbor::Type::Break) => break,
+                    Ok(_) => arr.push(decode_payment_method(d).map_err(|e| {
+                        format!(
+                            "decoding 'org.wasmcloud.examples.payments#PaymentMethod': {}",
+                            e
+                        )
+                    })?),
+                }
+            }
+            arr
+        }
+    };
+    Ok(__result)
+}
+/// A refund of a completed payment
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Refund {
+    /// Amount refunded, in cents
+    #[serde(default)]
+    pub amount: u32,
+    /// Optional reason for the refund
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Id of the refund from the RefundPaymentResponse
+    #[serde(rename = "refundId")]
+    #[serde(default)]
+    pub refund_id: String,
+    /// Timestamp (milliseconds since epoch, UTC)
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+// Encode Refund as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_refund<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &Refund,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.map(4)?;
+    e.str("amount")?;
+    e.u32(val.amount)?;
+    if let Some(val) = val.reason.as_ref() {
+        e.str("reason")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    e.str("refundId")?;
+    e.str(&val.refund_id)?;
+    e.str("timestamp")?;
+    e.u64(val.timestamp)?;
+    Ok(())
+}
+
+// Decode Refund from cbor input stream
+#[doc(hidden)]
+pub fn decode_refund(d: &mut wasmbus_rpc::cbor::Decoder<'_>) -> Result<Refund, RpcError> {
+    let __result = {
+        let mut amount: Option<u32> = None;
+        let mut reason: Option<Option<String>> = Some(None);
+        let mut refund_id: Option<String> = None;
+        let mut timestamp: Option<u64> = None;
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct Refund, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => amount = Some(d.u32()?),
+                    1 => {
+                        reason = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    2 => refund_id = Some(d.str()?.to_string()),
+                    3 => timestamp = Some(d.u64()?),
+                    _ => d.skip()?,
+                }
+            }
+        } else {
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "amount" => amount = Some(d.u32()?),
+                    "reason" => {
+                        reason = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "refundId" => refund_id = Some(d.str()?.to_string()),
+                    "timestamp" => timestamp = Some(d.u64()?),
+                    _ => d.skip()?,
+                }
+            }
+        }
+        Refund {
+            amount: if let Some(__x) = amount {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Refund.amount (#0)".to_string(),
+                ));
+            },
+            reason: reason.unwrap(),
+
+            refund_id: if let Some(__x) = refund_id {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Refund.refund_id (#2)".to_string(),
+                ));
+            },
+
+            timestamp: if let Some(__x) = timestamp {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Refund.timestamp (#3)".to_string(),
+                ));
+            },
+        }
+    };
+    Ok(__result)
+}
+/// Parameters sent for RefundPayment
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RefundPaymentRequest {
+    /// Amount to refund, in cents. If not set, everything that
+    /// hasn't already been refunded is refunded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<u32>,
+    /// Optional key that identifies retries: a repeated request with the
+    /// same key returns the result of the first one
+    #[serde(rename = "idempotencyKey")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    /// Optional reason for the refund
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Transaction id from the CompletePaymentResponse
+    #[serde(default)]
+    pub txid: String,
+}
+
+// Encode RefundPaymentRequest as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_refund_payment_request<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &RefundPaymentRequest,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.map(4)?;
+    if let Some(val) = val.amount.as_ref() {
+        e.str("amount")?;
+        e.u32(*val)?;
+    } else {
+        e.null()?;
+    }
+    if let Some(val) = val.idempotency_key.as_ref() {
+        e.str("idempotencyKey")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    if let Some(val) = val.reason.as_ref() {
+        e.str("reason")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    e.str("txid")?;
+    e.str(&val.txid)?;
+    Ok(())
+}
+
+// Decode RefundPaymentRequest from cbor input stream
+#[doc(hidden)]
+pub fn decode_refund_payment_request(
+    d: &mut wasmbus_rpc::cbor::Decoder<'_>,
+) -> Result<RefundPaymentRequest, RpcError> {
+    let __result = {
+        let mut amount: Option<Option<u32>> = Some(None);
+        let mut idempotency_key: Option<Option<String>> = Some(None);
+        let mut reason: Option<Option<String>> = Some(None);
+        let mut txid: Option<String> = None;
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct RefundPaymentRequest, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => {
+                        amount = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u32()?))
+                        }
+                    }
+                    1 => {
+                        idempotency_key = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    2 => {
+                        reason = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    3 => txid = Some(d.str()?.to_string()),
+                    _ => d.skip()?,
+                }
+            }
+        } else {
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "amount" => {
+                        amount = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.u32()?))
+                        }
+                    }
+                    "idempotencyKey" => {
+                        idempotency_key = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "reason" => {
+                        reason = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "txid" => txid = Some(d.str()?.to_string()),
+                    _ => d.skip()?,
+                }
+            }
+        }
+        RefundPaymentRequest {
+            amount: amount.unwrap(),
+            idempotency_key: idempotency_key.unwrap(),
+            reason: reason.unwrap(),
+
+            txid: if let Some(__x) = txid {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field RefundPaymentRequest.txid (#3)".to_string(),
+                ));
+            },
+        }
+    };
+    Ok(__result)
+}
+/// Response to RefundPayment
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RefundPaymentResponse {
+    /// Optional string w/rejection reason
+    #[serde(rename = "failReason")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_reason: Option<String>,
+    /// Optional string containing the id of the refund
+    #[serde(rename = "refundId")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refund_id: Option<String>,
+    /// Total amount refunded for the transaction, in cents, including this refund
+    #[serde(rename = "refundedAmount")]
+    #[serde(default)]
+    pub refunded_amount: u32,
+    /// Indicates a successful refund
+    #[serde(default)]
+    pub success: bool,
+    /// Timestamp (milliseconds since epoch, UTC)
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+// Encode RefundPaymentResponse as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_refund_payment_response<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &RefundPaymentResponse,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.map(5)?;
+    if let Some(val) = val.fail_reason.as_ref() {
+        e.str("failReason")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    if let Some(val) = val.refund_id.as_ref() {
+        e.str("refundId")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    e.str("refundedAmount")?;
+    e.u32(val.refunded_amount)?;
+    e.str("success")?;
+    e.bool(val.success)?;
+    e.str("timestamp")?;
+    e.u64(val.timestamp)?;
+    Ok(())
+}
+
+// Decode RefundPaymentResponse from cbor input stream
+#[doc(hidden)]
+pub fn decode_refund_payment_response(
+    d: &mut wasmbus_rpc::cbor::Decoder<'_>,
+) -> Result<RefundPaymentResponse, RpcError> {
+    let __result = {
+        let mut fail_reason: Option<Option<String>> = Some(None);
+        let mut refund_id: Option<Option<String>> = Some(None);
+        let mut refunded_amount: Option<u32> = None;
+        let mut success: Option<bool> = None;
+        let mut timestamp: Option<u64> = None;
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct RefundPaymentResponse, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => {
+                        fail_reason = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    1 => {
+                        refund_id = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    2 => refunded_amount = Some(d.u32()?),
+                    3 => success = Some(d.bool()?),
+                    4 => timestamp = Some(d.u64()?),
+                    _ => d.skip()?,
+                }
+            }
+        } else {
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "failReason" => {
+                        fail_reason = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "refundId" => {
+                        refund_id = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "refundedAmount" => refunded_amount = Some(d.u32()?),
+                    "success" => success = Some(d.bool()?),
+                    "timestamp" => timestamp = Some(d.u64()?),
+                    _ => d.skip()?,
+                }
+            }
+        }
+        RefundPaymentResponse {
+            fail_reason: fail_reason.unwrap(),
+            refund_id: refund_id.unwrap(),
+
+            refunded_amount: if let Some(__x) = refunded_amount {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field RefundPaymentResponse.refunded_amount (#2)".to_string(),
+                ));
+            },
+
+            success: if let Some(__x) = success {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field RefundPaymentResponse.success (#3)".to_string(),
+                ));
+            },
+
+            timestamp: if let Some(__x) = timestamp {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field RefundPaymentResponse.timestamp (#4)".to_string(),
+                ));
+            },
+        }
+    };
+    Ok(__result)
+}
+/// Refunds of a payment, oldest first
+pub type Refunds = Vec<Refund>;
+
+// Encode Refunds as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_refunds<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &Refunds,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.array(val.len() as u64)?;
+    for item in val.iter() {
+        encode_refund(e, item)?;
+    }
+    Ok(())
+}
+
+// Decode Refunds from cbor input stream
+#[doc(hidden)]
+pub fn decode_refunds(d: &mut wasmbus_rpc::cbor::Decoder<'_>) -> Result<Refunds, RpcError> {
+    let __result = {
+        if let Some(n) = d.array()? {
+            let mut arr: Vec<Refund> = Vec::with_capacity(n as usize);
+            for _ in 0..(n as usize) {
+                arr.push(decode_refund(d).map_err(|e| {
+                    format!("decoding 'org.wasmcloud.examples.payments#Refund': {}", e)
+                })?)
+            }
+            arr
+        } else {
+            // indefinite array
+            let mut arr: Vec<Refund> = Vec::new();
+            loop {
+                match d.datatype() {
+                    Err(_) => break,
+                    Ok(wasmbus_rpc::cbor::Type::Break) => break,
+                    Ok(_) => arr.push(decode_refund(d).map_err(|e| {
+                        format!("decoding 'org.wasmcloud.examples.payments#Refund': {}", e)
+                    })?),
+                }
+            }
+            arr
+        }
+    };
+    Ok(__result)
+}
+/// A completed payment
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Transaction {
+    /// authorization code of the payment
+    #[serde(rename = "authCode")]
+    #[serde(default)]
+    pub auth_code: String,
+    /// Amount authorized, in cents, including tax
+    #[serde(rename = "authorizedAmount")]
+    #[serde(default)]
+    pub authorized_amount: u32,
+    /// Amount captured, in cents, including tax
+    #[serde(rename = "capturedAmount")]
+    #[serde(default)]
+    pub captured_amount: u32,
+    /// Description from the CompletePaymentRequest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The entity (customer) that made the payment
+    #[serde(rename = "paymentEntity")]
+    #[serde(default)]
+    pub payment_entity: String,
+    /// Token of the payment method used
+    #[serde(rename = "paymentMethod")]
+    #[serde(default)]
+    pub payment_method: String,
+    /// Opaque Reference ID (e.g. order number) from the authorization
+    #[serde(rename = "referenceId")]
+    #[serde(default)]
+    pub reference_id: String,
+    /// Total of the refunds, in cents
+    #[serde(rename = "refundedAmount")]
+    #[serde(default)]
+    pub refunded_amount: u32,
+    /// Refunds of the payment
+    pub refunds: Refunds,
+    /// Time the payment was completed (milliseconds since epoch, UTC)
+    #[serde(default)]
+    pub timestamp: u64,
+    /// Transaction id issued by Payment provider
+    #[serde(default)]
+    pub txid: String,
+}
+
+// Encode Transaction as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_transaction<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &Transaction,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.map(11)?;
+    e.str("authCode")?;
+    e.str(&val.auth_code)?;
+    e.str("authorizedAmount")?;
+    e.u32(val.authorized_amount)?;
+    e.str("capturedAmount")?;
+    e.u32(val.captured_amount)?;
+    if let Some(val) = val.description.as_ref() {
+        e.str("description")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    e.str("paymentEntity")?;
+    e.str(&val.payment_entity)?;
+    e.str("paymentMethod")?;
+    e.str(&val.payment_method)?;
+    e.str("referenceId")?;
+    e.str(&val.reference_id)?;
+    e.str("refundedAmount")?;
+    e.u32(val.refunded_amount)?;
+    e.str("refunds")?;
+    encode_refunds(e, &val.refunds)?;
+    e.str("timestamp")?;
+    e.u64(val.timestamp)?;
+    e.str("txid")?;
+    e.str(&val.txid)?;
+    Ok(())
+}
+
+// Decode Transaction from cbor input stream
+#[doc(hidden)]
+pub fn decode_transaction(d: &mut wasmbus_rpc::cbor::Decoder<'_>) -> Result<Transaction, RpcError> {
+    let __result = {
+        let mut auth_code: Option<String> = None;
+        let mut authorized_amount: Option<u32> = None;
+        let mut captured_amount: Option<u32> = None;
+        let mut description: Option<Option<String>> = Some(None);
+        let mut payment_entity: Option<String> = None;
+        let mut payment_method: Option<String> = None;
+        let mut reference_id: Option<String> = None;
+        let mut refunded_amount: Option<u32> = None;
+        let mut refunds: Option<Refunds> = None;
+        let mut timestamp: Option<u64> = None;
+        let mut txid: Option<String> = None;
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct Transaction, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => auth_code = Some(d.str()?.to_string()),
+                    1 => authorized_amount = Some(d.u32()?),
+                    2 => captured_amount = Some(d.u32()?),
+                    3 => {
+                        description = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    4 => payment_entity = Some(d.str()?.to_string()),
+                    5 => payment_method = Some(d.str()?.to_string()),
+                    6 => reference_id = Some(d.str()?.to_string()),
+                    7 => refunded_amount = Some(d.u32()?),
+                    8 => {
+                        refunds = Some(decode_refunds(d).map_err(|e| {
+                            format!("decoding 'org.wasmcloud.examples.payments#Refunds': {}", e)
+                        })?)
+                    }
+                    9 => timestamp = Some(d.u64()?),
+                    10 => txid = Some(d.str()?.to_string()),
+                    _ => d.skip()?,
+                }
+            }
+        } else {
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "authCode" => auth_code = Some(d.str()?.to_string()),
+                    "authorizedAmount" => authorized_amount = Some(d.u32()?),
+                    "capturedAmount" => captured_amount = Some(d.u32()?),
+                    "description" => {
+                        description = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "paymentEntity" => payment_entity = Some(d.str()?.to_string()),
+                    "paymentMethod" => payment_method = Some(d.str()?.to_string()),
+                    "referenceId" => reference_id = Some(d.str()?.to_string()),
+                    "refundedAmount" => refunded_amount = Some(d.u32()?),
+                    "refunds" => {
+                        refunds = Some(decode_refunds(d).map_err(|e| {
+                            format!("decoding 'org.wasmcloud.examples.payments#Refunds': {}", e)
+                        })?)
+                    }
+                    "timestamp" => timestamp = Some(d.u64()?),
+                    "txid" => txid = Some(d.str()?.to_string()),
+                    _ => d.skip()?,
+                }
+            }
+        }
+        Transaction {
+            auth_code: if let Some(__x) = auth_code {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Transaction.auth_code (#0)".to_string(),
+                ));
+            },
+
+            authorized_amount: if let Some(__x) = authorized_amount {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Transaction.authorized_amount (#1)".to_string(),
+                ));
+            },
+
+            captured_amount: if let Some(__x) = captured_amount {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Transaction.captured_amount (#2)".to_string(),
+                ));
+            },
+            description: description.unwrap(),
+
+            payment_entity: if let Some(__x) = payment_entity {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Transaction.payment_entity (#4)".to_string(),
+                ));
+            },
+
+            payment_method: if let Some(__x) = payment_method {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Transaction.payment_method (#5)".to_string(),
+                ));
+            },
+
+            reference_id: if let Some(__x) = reference_id {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Transaction.reference_id (#6)".to_string(),
+                ));
+            },
+
+            refunded_amount: if let Some(__x) = refunded_amount {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Transaction.refunded_amount (#7)".to_string(),
+                ));
+            },
+
+            refunds: if let Some(__x) = refunds {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Transaction.refunds (#8)".to_string(),
+                ));
+            },
+
+            timestamp: if let Some(__x) = timestamp {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Transaction.timestamp (#9)".to_string(),
+                ));
+            },
+
+            txid: if let Some(__x) = txid {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field Transaction.txid (#10)".to_string(),
+                ));
+            },
+        }
+    };
+    Ok(__result)
+}
+/// A list of transactions
+pub type Transactions = Vec<Transaction>;
+
+// Encode Transactions as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_transactions<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &Transactions,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.array(val.len() as u64)?;
+    for item in val.iter() {
+        encode_transaction(e, item)?;
+    }
+    Ok(())
+}
+
+// Decode Transactions from cbor input stream
+#[doc(hidden)]
+pub fn decode_transactions(
+    d: &mut wasmbus_rpc::cbor::Decoder<'_>,
+) -> Result<Transactions, RpcError> {
+    let __result = {
+        if let Some(n) = d.array()? {
+            let mut arr: Vec<Transaction> = Vec::with_capacity(n as usize);
+            for _ in 0..(n as usize) {
+                arr.push(decode_transaction(d).map_err(|e| {
+                    format!(
+                        "decoding 'org.wasmcloud.examples.payments#Transaction': {}",
+                        e
+                    )
+                })?)
+            }
+            arr
+        } else {
+            // indefinite array
+            let mut arr: Vec<Transaction> = Vec::new();
+            loop {
+                match d.datatype() {
+                    Err(_) => break,
+                    Ok(wasmbus_rpc::cbor::Type::Break) => break,
+                    Ok(_) => arr.push(decode_transaction(d).map_err(|e| {
+                        format!(
+                            "decoding 'org.wasmcloud.examples.payments#Transaction': {}",
+                            e
+                        )
+                    })?),
+                }
+            }
+            arr
+        }
+    };
+    Ok(__result)
+}
+/// Parameters sent for VoidAuthorization
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct VoidAuthorizationRequest {
+    /// authorization code from the AuthorizePaymentResponse
+    #[serde(rename = "authCode")]
+    #[serde(default)]
+    pub auth_code: String,
+    /// Optional key that identifies retries: a repeated request with the
+    /// same key returns the result of the first one
+    #[serde(rename = "idempotencyKey")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+}
+
+// Encode VoidAuthorizationRequest as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_void_authorization_request<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &VoidAuthorizationRequest,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.map(2)?;
+    e.str("authCode")?;
+    e.str(&val.auth_code)?;
+    if let Some(val) = val.idempotency_key.as_ref() {
+        e.str("idempotencyKey")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    Ok(())
+}
+
+// Decode VoidAuthorizationRequest from cbor input stream
+#[doc(hidden)]
+pub fn decode_void_authorization_request(
+    d: &mut wasmbus_rpc::cbor::Decoder<'_>,
+) -> Result<VoidAuthorizationRequest, RpcError> {
+    let __result = {
+        let mut auth_code: Option<String> = None;
+        let mut idempotency_key: Option<Option<String>> = Some(None);
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct VoidAuthorizationRequest, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => auth_code = Some(d.str()?.to_string()),
+                    1 => {
+                        idempotency_key = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+
+                    _ => d.skip()?,
+                }
             }
-            arr
         } else {
-            // indefinite array
-            let mut arr: Vec<PaymentMethod> = Vec::new();
-            loop {
-                match d.datatype() {
-                    Err(_) => break,
-                    Ok(wasmbus_rpc::cbor::Type::Break) => break,
-                    Ok(_) => arr.push(decode_payment_method(d).map_err(|e| {
-                        format!(
-                            "decoding 'org.wasmcloud.examples.payments#PaymentMethod': {}",
-                            e
-                        )
-                    })?),
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "authCode" => auth_code = Some(d.str()?.to_string()),
+                    "idempotencyKey" => {
+                        idempotency_key = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    _ => d.skip()?,
                 }
             }
-            arr
+        }
+        VoidAuthorizationRequest {
+            auth_code: if let Some(__x) = auth_code {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field VoidAuthorizationRequest.auth_code (#0)".to_string(),
+                ));
+            },
+            idempotency_key: idempotency_key.unwrap(),
+        }
+    };
+    Ok(__result)
+}
+/// Response to VoidAuthorization
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct VoidAuthorizationResponse {
+    /// Optional string w/rejection reason
+    #[serde(rename = "failReason")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_reason: Option<String>,
+    /// Indicates the authorization was voided
+    #[serde(default)]
+    pub success: bool,
+}
+
+// Encode VoidAuthorizationResponse as CBOR and append to output stream
+#[doc(hidden)]
+#[allow(unused_mut)]
+pub fn encode_void_authorization_response<W: wasmbus_rpc::cbor::Write>(
+    mut e: &mut wasmbus_rpc::cbor::Encoder<W>,
+    val: &VoidAuthorizationResponse,
+) -> RpcResult<()>
+where
+    <W as wasmbus_rpc::cbor::Write>::Error: std::fmt::Display,
+{
+    e.map(2)?;
+    if let Some(val) = val.fail_reason.as_ref() {
+        e.str("failReason")?;
+        e.str(val)?;
+    } else {
+        e.null()?;
+    }
+    e.str("success")?;
+    e.bool(val.success)?;
+    Ok(())
+}
+
+// Decode VoidAuthorizationResponse from cbor input stream
+#[doc(hidden)]
+pub fn decode_void_authorization_response(
+    d: &mut wasmbus_rpc::cbor::Decoder<'_>,
+) -> Result<VoidAuthorizationResponse, RpcError> {
+    let __result = {
+        let mut fail_reason: Option<Option<String>> = Some(None);
+        let mut success: Option<bool> = None;
+
+        let is_array = match d.datatype()? {
+            wasmbus_rpc::cbor::Type::Array => true,
+            wasmbus_rpc::cbor::Type::Map => false,
+            _ => {
+                return Err(RpcError::Deser(
+                    "decoding struct VoidAuthorizationResponse, expected array or map".to_string(),
+                ))
+            }
+        };
+        if is_array {
+            let len = d.fixed_array()?;
+            for __i in 0..(len as usize) {
+                match __i {
+                    0 => {
+                        fail_reason = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    1 => success = Some(d.bool()?),
+                    _ => d.skip()?,
+                }
+            }
+        } else {
+            let len = d.fixed_map()?;
+            for __i in 0..(len as usize) {
+                match d.str()? {
+                    "failReason" => {
+                        fail_reason = if wasmbus_rpc::cbor::Type::Null == d.datatype()? {
+                            d.skip()?;
+                            Some(None)
+                        } else {
+                            Some(Some(d.str()?.to_string()))
+                        }
+                    }
+                    "success" => success = Some(d.bool()?),
+                    _ => d.skip()?,
+                }
+            }
+        }
+        VoidAuthorizationResponse {
+            fail_reason: fail_reason.unwrap(),
+
+            success: if let Some(__x) = success {
+                __x
+            } else {
+                return Err(RpcError::Deser(
+                    "missing field VoidAuthorizationResponse.success (#1)".to_string(),
+                ));
+            },
         }
     };
     Ok(__result)
@@ -672,7 +2229,8 @@ pub trait Payments {
     ) -> RpcResult<AuthorizePaymentResponse>;
     /// Completes a previously authorized payment.
     /// This operation requires the "authorization code" from a successful
-    /// authorization operation.
+    /// authorization operation. Less than the authorized amount may be captured,
+    /// and the rest of the authorization is released.
     async fn complete_payment(
         &self,
         ctx: &Context,
@@ -688,6 +2246,32 @@ pub trait Payments {
     /// supplied their credit card and user-friendly labels for those methods
     /// like "personal" and "work", etc.
     async fn get_payment_methods(&self, ctx: &Context) -> RpcResult<PaymentMethods>;
+    /// Returns a payment completed by the calling actor, and its refunds
+    async fn get_transaction<TS: ToString + ?Sized + std::marker::Sync>(
+        &self,
+        ctx: &Context,
+        arg: &TS,
+    ) -> RpcResult<GetTransactionResponse>;
+    /// Lists payments completed by the calling actor, oldest first, for reconciliation
+    async fn list_transactions(
+        &self,
+        ctx: &Context,
+        arg: &ListTransactionsRequest,
+    ) -> RpcResult<ListTransactionsResponse>;
+    /// Refunds all or part of a completed payment. The total refunded for a
+    /// payment can't exceed the amount captured by `CompletePayment`.
+    async fn refund_payment(
+        &self,
+        ctx: &Context,
+        arg: &RefundPaymentRequest,
+    ) -> RpcResult<RefundPaymentResponse>;
+    /// Cancels an authorization that hasn't been completed, so that its
+    /// auth code can't be used to complete a payment.
+    async fn void_authorization(
+        &self,
+        ctx: &Context,
+        arg: &VoidAuthorizationRequest,
+    ) -> RpcResult<VoidAuthorizationResponse>;
 }
 
 /// PaymentsReceiver receives messages defined in the Payments service trait
@@ -720,6 +2304,44 @@ pub trait PaymentsReceiver: MessageDispatch + Payments {
 
                 Ok(buf)
             }
+            "GetTransaction" => {
+                let value: String = wasmbus_rpc::common::deserialize(&message.arg)
+                    .map_err(|e| RpcError::Deser(format!("'String': {}", e)))?;
+
+                let resp = Payments::get_transaction(self, ctx, &value).await?;
+                let buf = wasmbus_rpc::common::serialize(&resp)?;
+
+                Ok(buf)
+            }
+            "ListTransactions" => {
+                let value: ListTransactionsRequest = wasmbus_rpc::common::deserialize(&message.arg)
+                    .map_err(|e| RpcError::Deser(format!("'ListTransactionsRequest': {}", e)))?;
+
+                let resp = Payments::list_transactions(self, ctx, &value).await?;
+                let buf = wasmbus_rpc::common::serialize(&resp)?;
+
+                Ok(buf)
+            }
+            "RefundPayment" => {
+                let value: RefundPaymentRequest = wasmbus_rpc::common::deserialize(&message.arg)
+                    .map_err(|e| RpcError::Deser(format!("'RefundPaymentRequest': {}", e)))?;
+
+                let resp = Payments::refund_payment(self, ctx, &value).await?;
+                let buf = wasmbus_rpc::common::serialize(&resp)?;
+
+                Ok(buf)
+            }
+            "VoidAuthorization" => {
+                let value: VoidAuthorizationRequest =
+                    wasmbus_rpc::common::deserialize(&message.arg).map_err(|e| {
+                        RpcError::Deser(format!("'VoidAuthorizationRequest': {}", e))
+                    })?;
+
+                let resp = Payments::void_authorization(self, ctx, &value).await?;
+                let buf = wasmbus_rpc::common::serialize(&resp)?;
+
+                Ok(buf)
+            }
             _ => Err(RpcError::MethodNotHandled(format!(
                 "Payments::{}",
                 message.method
@@ -802,7 +2424,8 @@ impl<T: Transport + std::marker::Sync + std::marker::Send> Payments for Payments
     #[allow(unused)]
     /// Completes a previously authorized payment.
     /// This operation requires the "authorization code" from a successful
-    /// authorization operation.
+    /// authorization operation. Less than the authorized amount may be captured,
+    /// and the rest of the authorization is released.
     async fn complete_payment(
         &self,
         ctx: &Context,
@@ -854,4 +2477,106 @@ impl<T: Transport + std::marker::Sync + std::marker::Send> Payments for Payments
             .map_err(|e| RpcError::Deser(format!("'{}': PaymentMethods", e)))?;
         Ok(value)
     }
+    #[allow(unused)]
+    /// Returns a payment completed by the calling actor, and its refunds
+    async fn get_transaction<TS: ToString + ?Sized + std::marker::Sync>(
+        &self,
+        ctx: &Context,
+        arg: &TS,
+    ) -> RpcResult<GetTransactionResponse> {
+        let buf = wasmbus_rpc::common::serialize(&arg.to_string())?;
+
+        let resp = self
+            .transport
+            .send(
+                ctx,
+                Message {
+                    method: "Payments.GetTransaction",
+                    arg: Cow::Borrowed(&buf),
+                },
+                None,
+            )
+            .await?;
+
+        let value: GetTransactionResponse = wasmbus_rpc::common::deserialize(&resp)
+            .map_err(|e| RpcError::Deser(format!("'{}': GetTransactionResponse", e)))?;
+        Ok(value)
+    }
+    #[allow(unused)]
+    /// Lists payments completed by the calling actor, oldest first, for reconciliation
+    async fn list_transactions(
+        &self,
+        ctx: &Context,
+        arg: &ListTransactionsRequest,
+    ) -> RpcResult<ListTransactionsResponse> {
+        let buf = wasmbus_rpc::common::serialize(arg)?;
+
+        let resp = self
+            .transport
+            .send(
+                ctx,
+                Message {
+                    method: "Payments.ListTransactions",
+                    arg: Cow::Borrowed(&buf),
+                },
+                None,
+            )
+            .await?;
+
+        let value: ListTransactionsResponse = wasmbus_rpc::common::deserialize(&resp)
+            .map_err(|e| RpcError::Deser(format!("'{}': ListTransactionsResponse", e)))?;
+        Ok(value)
+    }
+    #[allow(unused)]
+    /// Refunds all or part of a completed payment. The total refunded for a
+    /// payment can't exceed the amount captured by `CompletePayment`.
+    async fn refund_payment(
+        &self,
+        ctx: &Context,
+        arg: &RefundPaymentRequest,
+    ) -> RpcResult<RefundPaymentResponse> {
+        let buf = wasmbus_rpc::common::serialize(arg)?;
+
+        let resp = self
+            .transport
+            .send(
+                ctx,
+                Message {
+                    method: "Payments.RefundPayment",
+                    arg: Cow::Borrowed(&buf),
+                },
+                None,
+            )
+            .await?;
+
+        let value: RefundPaymentResponse = wasmbus_rpc::common::deserialize(&resp)
+            .map_err(|e| RpcError::Deser(format!("'{}': RefundPaymentResponse", e)))?;
+        Ok(value)
+    }
+    #[allow(unused)]
+    /// Cancels an authorization that hasn't been completed, so that its
+    /// auth code can't be used to complete a payment.
+    async fn void_authorization(
+        &self,
+        ctx: &Context,
+        arg: &VoidAuthorizationRequest,
+    ) -> RpcResult<VoidAuthorizationResponse> {
+        let buf = wasmbus_rpc::common::serialize(arg)?;
+
+        let resp = self
+            .transport
+            .send(
+                ctx,
+                Message {
+                    method: "Payments.VoidAuthorization",
+                    arg: Cow::Borrowed(&buf),
+                },
+                None,
+            )
+            .await?;
+
+        let value: VoidAuthorizationResponse = wasmbus_rpc::common::deserialize(&resp)
+            .map_err(|e| RpcError::Deser(format!("'{}': VoidAuthorizationResponse", e)))?;
+        Ok(value)
+    }
 }
@@ -0,0 +1,1051 @@
+//! In-memory state of the fake payment processor: wallets, authorizations,
+//! completed payments, and the results of requests with idempotency keys.
+//!
+//! Times are milliseconds since the epoch, passed in by the caller so that
+//! expiry can be tested without waiting.
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use once_cell::sync::Lazy;
+use serde::{de::DeserializeOwned, Serialize};
+use wasmbus_rpc::error::{RpcError, RpcResult};
+use wasmcloud_examples_payments::{
+    AuthorizePaymentRequest, AuthorizePaymentResponse, CompletePaymentRequest,
+    CompletePaymentResponse, GetTransactionResponse, ListTransactionsRequest,
+    ListTransactionsResponse, PaymentMethod, PaymentMethods, Refund, RefundPaymentRequest,
+    RefundPaymentResponse, Transaction, VoidAuthorizationRequest, VoidAuthorizationResponse,
+};
+
+use crate::scenario::Outcome;
+
+/// How long an authorization code can be used, unless the link sets `auth_ttl_secs`
+pub const DEFAULT_AUTH_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// How long expired auth codes are kept, so completing them reports that they expired
+const EXPIRED_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+/// How long the result of a request with an idempotency key is kept
+const REPLAY_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Number of transactions returned by `ListTransactions` unless the request sets `limit`
+const DEFAULT_LIST_LIMIT: u32 = 100;
+
+/// Descriptions of the payment methods in a new wallet
+const DEFAULT_METHODS: &[&str] = &["personal", "work"];
+
+/// When the provider started, which makes ids from different runs differ
+static START_MILLIS: Lazy<u64> = Lazy::new(now_millis);
+
+/// Returns the current time in milliseconds since the epoch
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// A successful authorization, which can be completed once before it expires
+#[derive(Clone, Debug)]
+struct Authorization {
+    /// Actor that requested the authorization, which is the only one that can use it
+    actor: String,
+    entity: String,
+    /// Token of the payment method
+    method: String,
+    reference_id: String,
+    /// Authorized amount, including tax
+    total: u32,
+    expires_at: u64,
+    status: AuthStatus,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum AuthStatus {
+    Open,
+    Voided,
+    /// Completed by the transaction with the id
+    Completed(String),
+}
+
+/// A completed payment
+#[derive(Clone, Debug)]
+struct Payment {
+    /// Sequence number of the transaction id, which orders payments for listing
+    seq: u64,
+    /// Actor that completed the payment, which is the only one that can see or refund it
+    actor: String,
+    txid: String,
+    auth_code: String,
+    entity: String,
+    method: String,
+    reference_id: String,
+    description: Option<String>,
+    /// Amount authorized, including tax
+    authorized: u32,
+    /// Amount captured, including tax
+    captured: u32,
+    refunds: Vec<Refund>,
+    /// When the payment was completed
+    timestamp: u64,
+}
+
+impl Payment {
+    /// Returns the total of the payment's refunds
+    fn refunded(&self) -> u32 {
+        self.refunds.iter().map(|r| r.amount).sum()
+    }
+
+    fn transaction(&self) -> Transaction {
+        Transaction {
+            txid: self.txid.clone(),
+            auth_code: self.auth_code.clone(),
+            payment_entity: self.entity.clone(),
+            payment_method: self.method.clone(),
+            reference_id: self.reference_id.clone(),
+            description: self.description.clone(),
+            authorized_amount: self.authorized,
+            captured_amount: self.captured,
+            refunded_amount: self.refunded(),
+            refunds: self.refunds.clone(),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+/// Identifies a request with an idempotency key: the actor, operation, and key
+type ReplayKey = (String, &'static str, String);
+
+/// The result of a request with an idempotency key, returned when it's repeated
+struct Replay {
+    /// The request as json, to detect a key reused for a different request
+    request: String,
+    response: serde_json::Value,
+    created: u64,
+}
+
+#[derive(Default)]
+struct State {
+    /// Payment methods of each payment entity
+    wallets: HashMap<String, PaymentMethods>,
+    /// Authorizations, keyed by auth code
+    authorizations: HashMap<String, Authorization>,
+    /// Completed payments, keyed by transaction id
+    payments: HashMap<String, Payment>,
+    /// Results of requests with idempotency keys
+    replays: HashMap<ReplayKey, Replay>,
+    /// Sequence number of the last id issued
+    last_id: u64,
+}
+
+impl State {
+    /// Returns a new id with the prefix
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.last_id += 1;
+        format!("{prefix}_{:x}{:06x}", *START_MILLIS, self.last_id)
+    }
+
+    /// Returns the entity's wallet, creating it with the default payment methods
+    fn wallet(&mut self, entity: &str) -> &PaymentMethods {
+        if !self.wallets.contains_key(entity) {
+            let methods = DEFAULT_METHODS
+                .iter()
+                .map(|description| PaymentMethod {
+                    token: Some(self.next_id("pm")),
+                    description: Some(description.to_string()),
+                })
+                .collect();
+            self.wallets.insert(entity.to_string(), methods);
+        }
+        self.wallets.get(entity).unwrap()
+    }
+
+    /// Authorizes a payment from one of the entity's payment methods, or with
+    /// the scripted outcome
+    fn authorize(
+        &mut self,
+        actor: &str,
+        req: &AuthorizePaymentRequest,
+        outcome: Option<Outcome>,
+        now: u64,
+        ttl: Duration,
+    ) -> AuthorizePaymentResponse {
+        match outcome {
+            None => {}
+            Some(Outcome::Approve) => return self.approve(actor, req, now, ttl),
+            Some(Outcome::ExpiredAuth) => return self.approve(actor, req, now, Duration::ZERO),
+            Some(outcome) => return declined(outcome.fail_reason().unwrap_or_default()),
+        }
+        if req.amount == 0 {
+            return declined("amount must be greater than zero");
+        }
+        if req.amount.checked_add(req.tax).is_none() {
+            return declined("amount and tax are too large");
+        }
+        let known = self
+            .wallet(&req.payment_entity)
+            .iter()
+            .any(|m| m.token.as_deref() == Some(req.payment_method.as_str()));
+        if !known {
+            return declined("unknown payment method");
+        }
+        self.approve(actor, req, now, ttl)
+    }
+
+    /// Issues an auth code that can be used until `ttl` after `now`
+    fn approve(
+        &mut self,
+        actor: &str,
+        req: &AuthorizePaymentRequest,
+        now: u64,
+        ttl: Duration,
+    ) -> AuthorizePaymentResponse {
+        let auth_code = self.next_id("auth");
+        self.authorizations.insert(
+            auth_code.clone(),
+            Authorization {
+                actor: actor.to_string(),
+                entity: req.payment_entity.clone(),
+                method: req.payment_method.clone(),
+                reference_id: req.reference_id.clone(),
+                total: req.amount.saturating_add(req.tax),
+                expires_at: now.saturating_add(ttl.as_millis() as u64),
+                status: AuthStatus::Open,
+            },
+        );
+        AuthorizePaymentResponse {
+            success: true,
+            auth_code: Some(auth_code),
+            fail_reason: None,
+        }
+    }
+
+    fn complete(
+        &mut self,
+        actor: &str,
+        req: &CompletePaymentRequest,
+        now: u64,
+    ) -> RpcResult<CompletePaymentResponse> {
+        let auth = match self
+            .authorizations
+            .get(&req.auth_code)
+            .filter(|auth| auth.actor == actor)
+        {
+            Some(auth) => auth.clone(),
+            None => {
+                return Err(RpcError::InvalidParameter(format!(
+                    "unknown auth code '{}'",
+                    &req.auth_code
+                )))
+            }
+        };
+        match &auth.status {
+            AuthStatus::Open => {}
+            AuthStatus::Voided => {
+                return Err(RpcError::InvalidParameter(format!(
+                    "auth code '{}' was voided",
+                    &req.auth_code
+                )))
+            }
+            AuthStatus::Completed(txid) => {
+                return Err(RpcError::InvalidParameter(format!(
+                    "auth code '{}' was already used by transaction {}",
+                    &req.auth_code, txid
+                )))
+            }
+        }
+        if now >= auth.expires_at {
+            return Err(RpcError::InvalidParameter(format!(
+                "auth code '{}' has expired",
+                &req.auth_code
+            )));
+        }
+        let amount = req.amount.unwrap_or(auth.total);
+        if amount == 0 {
+            return Err(RpcError::InvalidParameter(
+                "amount to capture must be greater than zero".to_string(),
+            ));
+        }
+        if amount > auth.total {
+            return Err(RpcError::InvalidParameter(format!(
+                "can't capture {} cents, only {} were authorized",
+                amount, auth.total
+            )));
+        }
+        let txid = self.next_id("tx");
+        if let Some(auth) = self.authorizations.get_mut(&req.auth_code) {
+            auth.status = AuthStatus::Completed(txid.clone());
+        }
+        self.payments.insert(
+            txid.clone(),
+            Payment {
+                seq: self.last_id,
+                actor: actor.to_string(),
+                txid: txid.clone(),
+                auth_code: req.auth_code.clone(),
+                entity: auth.entity,
+                method: auth.method,
+                reference_id: auth.reference_id,
+                description: req.description.clone(),
+                authorized: auth.total,
+                captured: amount,
+                refunds: Vec::new(),
+                timestamp: now,
+            },
+        );
+        Ok(CompletePaymentResponse {
+            success: true,
+            txid,
+            timestamp: now,
+            amount: Some(amount),
+        })
+    }
+
+    fn void(
+        &mut self,
+        actor: &str,
+        req: &VoidAuthorizationRequest,
+        now: u64,
+    ) -> VoidAuthorizationResponse {
+        let auth = match self
+            .authorizations
+            .get_mut(&req.auth_code)
+            .filter(|auth| auth.actor == actor)
+        {
+            Some(auth) => auth,
+            None => return void_failed("unknown auth code"),
+        };
+        match &auth.status {
+            AuthStatus::Open if now >= auth.expires_at => {
+                void_failed("authorization has already expired")
+            }
+            AuthStatus::Open => {
+                auth.status = AuthStatus::Voided;
+                VoidAuthorizationResponse {
+                    success: true,
+                    fail_reason: None,
+                }
+            }
+            AuthStatus::Voided => void_failed("authorization was already voided"),
+            AuthStatus::Completed(_) => {
+                void_failed("payment was already completed, refund it instead")
+            }
+        }
+    }
+
+    fn refund(
+        &mut self,
+        actor: &str,
+        req: &RefundPaymentRequest,
+        now: u64,
+    ) -> RefundPaymentResponse {
+        let (captured, refunded) = match self
+            .payments
+            .get(&req.txid)
+            .filter(|payment| payment.actor == actor)
+        {
+            Some(payment) => (payment.captured, payment.refunded()),
+            None => return refund_failed("unknown transaction", 0, now),
+        };
+        let refundable = captured - refunded;
+        if refundable == 0 {
+            return refund_failed("payment was already fully refunded", refunded, now);
+        }
+        let amount = req.amount.unwrap_or(refundable);
+        if amount == 0 {
+            return refund_failed("refund amount must be greater than zero", refunded, now);
+        }
+        if amount > refundable {
+            return refund_failed(
+                &format!(
+                    "can't refund {} cents, only {} cents can be refunded",
+                    amount, refundable
+                ),
+                refunded,
+                now,
+            );
+        }
+        let refund_id = self.next_id("re");
+        if let Some(payment) = self.payments.get_mut(&req.txid) {
+            payment.refunds.push(Refund {
+                refund_id: refund_id.clone(),
+                amount,
+                reason: req.reason.clone(),
+                timestamp: now,
+            });
+        }
+        RefundPaymentResponse {
+            success: true,
+            refund_id: Some(refund_id),
+            fail_reason: None,
+            refunded_amount: refunded + amount,
+            timestamp: now,
+        }
+    }
+}
+
+/// Payment state shared by all links
+#[derive(Clone, Default)]
+pub struct Ledger {
+    state: Arc<Mutex<State>>,
+}
+
+impl Ledger {
+    /// Returns the payment methods of an entity
+    pub fn payment_methods(&self, entity: &str) -> PaymentMethods {
+        self.state.lock().unwrap().wallet(entity).clone()
+    }
+
+    /// Authorizes a payment from one of the entity's payment methods, or with the
+    /// scripted outcome if there is one. The auth code can be used to complete the
+    /// payment until `ttl` after `now`.
+    pub fn authorize(
+        &self,
+        actor: &str,
+        req: &AuthorizePaymentRequest,
+        outcome: Option<Outcome>,
+        now: u64,
+        ttl: Duration,
+    ) -> RpcResult<AuthorizePaymentResponse> {
+        self.idempotent(
+            (actor, "AuthorizePayment", req.idempotency_key.as_ref()),
+            req,
+            now,
+            |state| Ok(state.authorize(actor, req, outcome, now, ttl)),
+        )
+    }
+
+    /// Completes a payment authorized by the actor, capturing up to the authorized
+    /// amount. Each auth code can only be used once, and not after it expires or is voided.
+    pub fn complete(
+        &self,
+        actor: &str,
+        req: &CompletePaymentRequest,
+        now: u64,
+    ) -> RpcResult<CompletePaymentResponse> {
+        self.idempotent(
+            (actor, "CompletePayment", req.idempotency_key.as_ref()),
+            req,
+            now,
+            |state| state.complete(actor, req, now),
+        )
+    }
+
+    /// Voids an authorization of the actor that hasn't been completed or expired
+    pub fn void(
+        &self,
+        actor: &str,
+        req: &VoidAuthorizationRequest,
+        now: u64,
+    ) -> RpcResult<VoidAuthorizationResponse> {
+        self.idempotent(
+            (actor, "VoidAuthorization", req.idempotency_key.as_ref()),
+            req,
+            now,
+            |state| Ok(state.void(actor, req, now)),
+        )
+    }
+
+    /// Refunds some or all of a payment completed by the actor. Refunds can't add
+    /// up to more than the captured amount.
+    pub fn refund(
+        &self,
+        actor: &str,
+        req: &RefundPaymentRequest,
+        now: u64,
+    ) -> RpcResult<RefundPaymentResponse> {
+        self.idempotent(
+            (actor, "RefundPayment", req.idempotency_key.as_ref()),
+            req,
+            now,
+            |state| Ok(state.refund(actor, req, now)),
+        )
+    }
+
+    /// Returns a payment completed by the actor
+    pub fn transaction(&self, actor: &str, txid: &str) -> GetTransactionResponse {
+        let transaction = self
+            .state
+            .lock()
+            .unwrap()
+            .payments
+            .get(txid)
+            .filter(|p| p.actor == actor)
+            .map(Payment::transaction);
+        GetTransactionResponse {
+            found: transaction.is_some(),
+            transaction,
+        }
+    }
+
+    /// Lists payments completed by the actor that match the request's filters, oldest
+    /// first. `nextStart` is the sequence number of the first payment that didn't fit.
+    pub fn list(
+        &self,
+        actor: &str,
+        req: &ListTransactionsRequest,
+    ) -> RpcResult<ListTransactionsResponse> {
+        let start = match &req.start {
+            Some(start) => start
+                .parse::<u64>()
+                .map_err(|_| RpcError::InvalidParameter(format!("invalid start '{}'", start)))?,
+            None => 0,
+        };
+        let limit = req.limit.unwrap_or(DEFAULT_LIST_LIMIT).max(1) as usize;
+        let state = self.state.lock().unwrap();
+        let mut payments = state
+            .payments
+            .values()
+            .filter(|p| {
+                p.actor == actor
+                    && p.seq >= start
+                    && req.payment_entity.as_ref().map_or(true, |e| e == &p.entity)
+                    && req
+                        .reference_id
+                        .as_ref()
+                        .map_or(true, |r| r == &p.reference_id)
+                    && req.since.map_or(true, |since| p.timestamp >= since)
+                    && req.until.map_or(true, |until| p.timestamp < until)
+            })
+            .collect::<Vec<_>>();
+        payments.sort_by_key(|p| p.seq);
+        Ok(ListTransactionsResponse {
+            next_start: payments.get(limit).map(|p| p.seq.to_string()),
+            transactions: payments
+                .iter()
+                .take(limit)
+                .map(|p| p.transaction())
+                .collect(),
+        })
+    }
+
+    /// Forgets authorizations that expired a while ago without being completed,
+    /// and results of requests with idempotency keys that are too old to be retried
+    pub fn remove_expired(&self, now: u64) {
+        let retention = EXPIRED_RETENTION.as_millis() as u64;
+        let replay_retention = REPLAY_RETENTION.as_millis() as u64;
+        let mut state = self.state.lock().unwrap();
+        state.authorizations.retain(|_, auth| {
+            matches!(auth.status, AuthStatus::Completed(_))
+                || now < auth.expires_at.saturating_add(retention)
+        });
+        state
+            .replays
+            .retain(|_, replay| now < replay.created.saturating_add(replay_retention));
+    }
+
+    /// Runs a request, unless a request with the same idempotency key was already
+    /// run, in which case its response is returned. The check and the request run
+    /// under one lock, so concurrent retries can't both run. Errors aren't recorded,
+    /// so the request can be retried after an error.
+    fn idempotent<Req, Resp>(
+        &self,
+        (actor, operation, key): (&str, &'static str, Option<&String>),
+        req: &Req,
+        now: u64,
+        run: impl FnOnce(&mut State) -> RpcResult<Resp>,
+    ) -> RpcResult<Resp>
+    where
+        Req: Serialize,
+        Resp: Serialize + DeserializeOwned,
+    {
+        let mut state = self.state.lock().unwrap();
+        let key = match key {
+            Some(key) => (actor.to_string(), operation, key.clone()),
+            None => return run(&mut state),
+        };
+        let request = serde_json::to_string(req).map_err(|e| RpcError::Ser(e.to_string()))?;
+        if let Some(replay) = state.replays.get(&key) {
+            if replay.request != request {
+                return Err(RpcError::InvalidParameter(format!(
+                    "idempotency key '{}' was already used for a different {} request",
+                    &key.2, operation
+                )));
+            }
+            return serde_json::from_value(replay.response.clone())
+                .map_err(|e| RpcError::Deser(e.to_string()));
+        }
+        let resp = run(&mut state)?;
+        let response = serde_json::to_value(&resp).map_err(|e| RpcError::Ser(e.to_string()))?;
+        state.replays.insert(
+            key,
+            Replay {
+                request,
+                response,
+                created: now,
+            },
+        );
+        Ok(resp)
+    }
+}
+
+/// Returns a declined authorization
+fn declined(reason: &str) -> AuthorizePaymentResponse {
+    AuthorizePaymentResponse {
+        success: false,
+        auth_code: None,
+        fail_reason: Some(reason.to_string()),
+    }
+}
+
+fn void_failed(reason: &str) -> VoidAuthorizationResponse {
+    VoidAuthorizationResponse {
+        success: false,
+        fail_reason: Some(reason.to_string()),
+    }
+}
+
+fn refund_failed(reason: &str, refunded: u32, now: u64) -> RefundPaymentResponse {
+    RefundPaymentResponse {
+        success: false,
+        refund_id: None,
+        fail_reason: Some(reason.to_string()),
+        refunded_amount: refunded,
+        timestamp: now,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+    const ACTOR: &str = "MACTOR";
+
+    fn authorize(
+        ledger: &Ledger,
+        req: &AuthorizePaymentRequest,
+        now: u64,
+    ) -> AuthorizePaymentResponse {
+        ledger.authorize(ACTOR, req, None, now, TTL).unwrap()
+    }
+
+    fn refund(ledger: &Ledger, req: &RefundPaymentRequest, now: u64) -> RefundPaymentResponse {
+        ledger.refund(ACTOR, req, now).unwrap()
+    }
+
+    fn void(ledger: &Ledger, req: &VoidAuthorizationRequest) -> VoidAuthorizationResponse {
+        ledger.void(ACTOR, req, 0).unwrap()
+    }
+
+    fn request(ledger: &Ledger, entity: &str, amount: u32) -> AuthorizePaymentRequest {
+        let methods = ledger.payment_methods(entity);
+        AuthorizePaymentRequest {
+            amount,
+            tax: 10,
+            payment_method: methods[0].token.clone().unwrap(),
+            payment_entity: entity.to_string(),
+            reference_id: "order-1".to_string(),
+            idempotency_key: None,
+        }
+    }
+
+    fn complete_request(auth_code: &str) -> CompletePaymentRequest {
+        CompletePaymentRequest {
+            auth_code: auth_code.to_string(),
+            description: Some("pet food".to_string()),
+            amount: None,
+            idempotency_key: None,
+        }
+    }
+
+    fn void_request(auth_code: &str) -> VoidAuthorizationRequest {
+        VoidAuthorizationRequest {
+            auth_code: auth_code.to_string(),
+            idempotency_key: None,
+        }
+    }
+
+    /// Returns the transaction id of a completed payment of `amount` plus 10 tax
+    fn paid(ledger: &Ledger, amount: u32) -> String {
+        let resp = authorize(ledger, &request(ledger, "alice", amount), 0);
+        let done = ledger
+            .complete(ACTOR, &complete_request(&resp.auth_code.unwrap()), 1)
+            .unwrap();
+        assert_eq!(done.amount, Some(amount + 10));
+        done.txid
+    }
+
+    fn refund_request(txid: &str, amount: Option<u32>) -> RefundPaymentRequest {
+        RefundPaymentRequest {
+            txid: txid.to_string(),
+            amount,
+            reason: None,
+            idempotency_key: None,
+        }
+    }
+
+    #[test]
+    fn wallets_are_per_entity() {
+        let ledger = Ledger::default();
+        let alice = ledger.payment_methods("alice");
+        let bob = ledger.payment_methods("bob");
+        assert_eq!(alice.len(), DEFAULT_METHODS.len());
+        assert_eq!(alice, ledger.payment_methods("alice"));
+        assert_ne!(alice[0].token, bob[0].token);
+
+        // bob can't pay with alice's card
+        let mut req = request(&ledger, "bob", 100);
+        req.payment_method = alice[0].token.clone().unwrap();
+        let resp = authorize(&ledger, &req, 0);
+        assert!(!resp.success);
+        assert!(resp.auth_code.is_none());
+        assert_eq!(resp.fail_reason.as_deref(), Some("unknown payment method"));
+    }
+
+    #[test]
+    fn authorize_and_complete() {
+        let ledger = Ledger::default();
+        let resp = authorize(&ledger, &request(&ledger, "alice", 100), 1000);
+        assert!(resp.success);
+        let auth_code = resp.auth_code.unwrap();
+
+        let done = ledger
+            .complete(ACTOR, &complete_request(&auth_code), 2000)
+            .unwrap();
+        assert!(done.success);
+        assert!(done.txid.starts_with("tx_"));
+        assert_eq!(done.timestamp, 2000);
+    }
+
+    #[test]
+    fn auth_codes_are_used_once() {
+        let ledger = Ledger::default();
+        let resp = authorize(&ledger, &request(&ledger, "alice", 100), 0);
+        let auth_code = resp.auth_code.unwrap();
+        ledger
+            .complete(ACTOR, &complete_request(&auth_code), 1)
+            .unwrap();
+        assert!(matches!(
+            ledger.complete(ACTOR, &complete_request(&auth_code), 2),
+            Err(RpcError::InvalidParameter(_))
+        ));
+        assert!(ledger
+            .complete(ACTOR, &complete_request("auth_0"), 2)
+            .is_err());
+    }
+
+    #[test]
+    fn auth_codes_expire() {
+        let ledger = Ledger::default();
+        let resp = authorize(&ledger, &request(&ledger, "alice", 100), 0);
+        let auth_code = resp.auth_code.unwrap();
+        let expired = TTL.as_millis() as u64;
+        assert!(ledger
+            .complete(ACTOR, &complete_request(&auth_code), expired)
+            .is_err());
+        assert!(ledger
+            .complete(ACTOR, &complete_request(&auth_code), expired - 1)
+            .is_ok());
+
+        let resp = authorize(&ledger, &request(&ledger, "alice", 100), 0);
+        let auth_code = resp.auth_code.unwrap();
+        ledger.remove_expired(expired);
+        let err = ledger
+            .complete(ACTOR, &complete_request(&auth_code), expired)
+            .unwrap_err();
+        assert!(err.to_string().contains("expired"), "{err}");
+        ledger.remove_expired(expired + EXPIRED_RETENTION.as_millis() as u64);
+        let err = ledger
+            .complete(ACTOR, &complete_request(&auth_code), 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown auth code"), "{err}");
+    }
+
+    #[test]
+    fn invalid_amounts_are_declined() {
+        let ledger = Ledger::default();
+        let resp = authorize(&ledger, &request(&ledger, "alice", 0), 0);
+        assert!(!resp.success);
+        let resp = authorize(&ledger, &request(&ledger, "alice", u32::MAX), 0);
+        assert!(!resp.success);
+    }
+
+    #[test]
+    fn partial_capture() {
+        let ledger = Ledger::default();
+        let resp = authorize(&ledger, &request(&ledger, "alice", 100), 0);
+        let auth_code = resp.auth_code.unwrap();
+        let mut req = complete_request(&auth_code);
+        req.amount = Some(111);
+        assert!(
+            ledger.complete(ACTOR, &req, 1).is_err(),
+            "more than authorized"
+        );
+        req.amount = Some(0);
+        assert!(ledger.complete(ACTOR, &req, 1).is_err());
+        req.amount = Some(60);
+        let done = ledger.complete(ACTOR, &req, 1).unwrap();
+        assert_eq!(done.amount, Some(60));
+
+        // only the captured amount can be refunded
+        let resp = refund(&ledger, &refund_request(&done.txid, Some(61)), 2);
+        assert!(!resp.success);
+        let resp = refund(&ledger, &refund_request(&done.txid, None), 2);
+        assert!(resp.success);
+        assert_eq!(resp.refunded_amount, 60);
+    }
+
+    #[test]
+    fn void_authorization() {
+        let ledger = Ledger::default();
+        let resp = authorize(&ledger, &request(&ledger, "alice", 100), 0);
+        let auth_code = resp.auth_code.unwrap();
+        assert!(void(&ledger, &void_request(&auth_code)).success);
+        let again = void(&ledger, &void_request(&auth_code));
+        assert!(!again.success);
+        assert_eq!(
+            again.fail_reason.as_deref(),
+            Some("authorization was already voided")
+        );
+        let err = ledger
+            .complete(ACTOR, &complete_request(&auth_code), 1)
+            .unwrap_err();
+        assert!(err.to_string().contains("voided"), "{err}");
+
+        // completed payments can't be voided
+        let resp = authorize(&ledger, &request(&ledger, "alice", 100), 0);
+        let auth_code = resp.auth_code.unwrap();
+        ledger
+            .complete(ACTOR, &complete_request(&auth_code), 1)
+            .unwrap();
+        assert!(!void(&ledger, &void_request(&auth_code)).success);
+        assert!(!void(&ledger, &void_request("auth_0")).success);
+
+        // nor can expired ones
+        let resp = authorize(&ledger, &request(&ledger, "alice", 100), 0);
+        let auth_code = resp.auth_code.unwrap();
+        let expired = ledger
+            .void(ACTOR, &void_request(&auth_code), TTL.as_millis() as u64)
+            .unwrap();
+        assert_eq!(
+            expired.fail_reason.as_deref(),
+            Some("authorization has already expired")
+        );
+    }
+
+    #[test]
+    fn payments_are_scoped_to_the_actor() {
+        const OTHER: &str = "MOTHER";
+        let ledger = Ledger::default();
+        let txid = paid(&ledger, 100);
+        let resp = authorize(&ledger, &request(&ledger, "alice", 100), 0);
+        let auth_code = resp.auth_code.unwrap();
+
+        // another actor can't see, refund, complete, or void them
+        assert!(!ledger.transaction(OTHER, &txid).found);
+        let listed = ledger.list(OTHER, &Default::default()).unwrap();
+        assert!(listed.transactions.is_empty());
+        let refunded = ledger
+            .refund(OTHER, &refund_request(&txid, None), 2)
+            .unwrap();
+        assert_eq!(refunded.fail_reason.as_deref(), Some("unknown transaction"));
+        let err = ledger
+            .complete(OTHER, &complete_request(&auth_code), 2)
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown auth code"), "{err}");
+        let voided = ledger.void(OTHER, &void_request(&auth_code), 2).unwrap();
+        assert_eq!(voided.fail_reason.as_deref(), Some("unknown auth code"));
+
+        // and the actor's payments are unchanged
+        let tx = ledger.transaction(ACTOR, &txid).transaction.unwrap();
+        assert_eq!(tx.refunded_amount, 0);
+        assert!(void(&ledger, &void_request(&auth_code)).success);
+    }
+
+    #[test]
+    fn refunds_are_limited_to_captured_amount() {
+        let ledger = Ledger::default();
+        let txid = paid(&ledger, 100);
+
+        let first = refund(&ledger, &refund_request(&txid, Some(30)), 2);
+        assert!(first.success);
+        assert_eq!(first.refunded_amount, 30);
+        let second = refund(&ledger, &refund_request(&txid, Some(50)), 3);
+        assert!(second.success);
+        assert_eq!(second.refunded_amount, 80);
+        assert_ne!(first.refund_id, second.refund_id);
+
+        let too_much = refund(&ledger, &refund_request(&txid, Some(31)), 4);
+        assert!(!too_much.success);
+        assert!(too_much.refund_id.is_none());
+        assert_eq!(too_much.refunded_amount, 80);
+
+        let rest = refund(&ledger, &refund_request(&txid, None), 5);
+        assert!(rest.success);
+        assert_eq!(rest.refunded_amount, 110);
+        let done = refund(&ledger, &refund_request(&txid, None), 6);
+        assert_eq!(
+            done.fail_reason.as_deref(),
+            Some("payment was already fully refunded")
+        );
+
+        let unknown = refund(&ledger, &refund_request("tx_0", None), 7);
+        assert_eq!(unknown.fail_reason.as_deref(), Some("unknown transaction"));
+        let zero = refund(&ledger, &refund_request(&paid(&ledger, 5), Some(0)), 8);
+        assert!(!zero.success);
+    }
+
+    #[test]
+    fn repeated_requests_return_first_result() {
+        let ledger = Ledger::default();
+        let mut req = request(&ledger, "alice", 100);
+        req.idempotency_key = Some("auth-1".to_string());
+        let first = authorize(&ledger, &req, 0);
+        assert_eq!(authorize(&ledger, &req, 1), first);
+
+        // a retry after a timeout doesn't capture the payment again
+        let mut complete = complete_request(&first.auth_code.unwrap());
+        complete.idempotency_key = Some("complete-1".to_string());
+        let done = ledger.complete(ACTOR, &complete, 2).unwrap();
+        let again = ledger.complete(ACTOR, &complete, 3).unwrap();
+        assert_eq!(again, done);
+        assert_eq!(
+            ledger
+                .list(ACTOR, &Default::default())
+                .unwrap()
+                .transactions
+                .len(),
+            1
+        );
+
+        let mut refund_req = refund_request(&done.txid, Some(10));
+        refund_req.idempotency_key = Some("refund-1".to_string());
+        let refunded = refund(&ledger, &refund_req, 4);
+        assert_eq!(refund(&ledger, &refund_req, 5), refunded);
+        let tx = ledger.transaction(ACTOR, &done.txid).transaction.unwrap();
+        assert_eq!(tx.refunded_amount, 10);
+
+        // keys are scoped to the actor
+        let err = ledger.complete("MOTHER", &complete, 6).unwrap_err();
+        assert!(err.to_string().contains("unknown auth code"), "{err}");
+
+        // results are kept for a day
+        ledger.remove_expired(3 + REPLAY_RETENTION.as_millis() as u64);
+        assert!(ledger.complete(ACTOR, &complete, 7).is_err());
+    }
+
+    #[test]
+    fn keys_are_checked_against_the_request() {
+        let ledger = Ledger::default();
+        let mut req = request(&ledger, "alice", 100);
+        req.idempotency_key = Some("auth-1".to_string());
+        let first = authorize(&ledger, &req, 0);
+
+        req.amount = 200;
+        let err = ledger.authorize(ACTOR, &req, None, 0, TTL).unwrap_err();
+        assert!(matches!(err, RpcError::InvalidParameter(_)), "{err}");
+
+        // errors aren't recorded, so the request can be retried with the same key
+        let mut complete = complete_request(&first.auth_code.unwrap());
+        complete.idempotency_key = Some("complete-1".to_string());
+        complete.amount = Some(1000);
+        assert!(ledger.complete(ACTOR, &complete, 1).is_err());
+        complete.amount = None;
+        let done = ledger.complete(ACTOR, &complete, 1).unwrap();
+        complete.amount = Some(50);
+        let err = ledger.complete(ACTOR, &complete, 2).unwrap_err();
+        assert!(err.to_string().contains("different"), "{err}");
+        complete.amount = None;
+        assert_eq!(ledger.complete(ACTOR, &complete, 3).unwrap(), done);
+    }
+
+    #[test]
+    fn scripted_outcomes_are_replayed() {
+        let ledger = Ledger::default();
+        let mut req = request(&ledger, "alice", 100);
+        req.idempotency_key = Some("auth-1".to_string());
+        let declined = ledger
+            .authorize(ACTOR, &req, Some(Outcome::InsufficientFunds), 0, TTL)
+            .unwrap();
+        assert!(!declined.success);
+        assert_eq!(authorize(&ledger, &req, 1), declined);
+    }
+
+    #[test]
+    fn transaction_lookup() {
+        let ledger = Ledger::default();
+        let txid = paid(&ledger, 100);
+        refund(&ledger, &refund_request(&txid, Some(30)), 2);
+
+        let found = ledger.transaction(ACTOR, &txid);
+        assert!(found.found);
+        let tx = found.transaction.unwrap();
+        assert_eq!(tx.txid, txid);
+        assert_eq!(tx.payment_entity, "alice");
+        assert_eq!(tx.reference_id, "order-1");
+        assert_eq!(tx.description.as_deref(), Some("pet food"));
+        assert_eq!(tx.authorized_amount, 110);
+        assert_eq!(tx.captured_amount, 110);
+        assert_eq!(tx.refunded_amount, 30);
+        assert_eq!(tx.refunds.len(), 1);
+        assert_eq!(tx.refunds[0].timestamp, 2);
+        assert_eq!(tx.timestamp, 1);
+
+        let missing = ledger.transaction(ACTOR, "tx_0");
+        assert!(!missing.found);
+        assert!(missing.transaction.is_none());
+    }
+
+    #[test]
+    fn list_transactions() {
+        let ledger = Ledger::default();
+        let txids = (1..=5).map(|n| paid(&ledger, n)).collect::<Vec<_>>();
+        let mut req = request(&ledger, "bob", 100);
+        req.reference_id = "order-2".to_string();
+        let resp = authorize(&ledger, &req, 0);
+        ledger
+            .complete(ACTOR, &complete_request(&resp.auth_code.unwrap()), 10)
+            .unwrap();
+
+        let all = ledger.list(ACTOR, &Default::default()).unwrap();
+        assert_eq!(all.transactions.len(), 6);
+        assert!(all.next_start.is_none());
+        let listed = all
+            .transactions
+            .iter()
+            .map(|t| t.txid.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(listed[..5], txids[..]);
+
+        // pages
+        let mut page = ListTransactionsRequest {
+            payment_entity: Some("alice".to_string()),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let mut pages = Vec::new();
+        loop {
+            let resp = ledger.list(ACTOR, &page).unwrap();
+            pages.push(resp.transactions.len());
+            match resp.next_start {
+                Some(start) => page.start = Some(start),
+                None => break,
+            }
+        }
+        assert_eq!(pages, vec![2, 2, 1]);
+
+        // filters
+        let bob = ListTransactionsRequest {
+            reference_id: Some("order-2".to_string()),
+            ..Default::default()
+        };
+        let bob = ledger.list(ACTOR, &bob).unwrap().transactions;
+        assert_eq!(bob.len(), 1);
+        assert_eq!(bob[0].payment_entity, "bob");
+        let window = ListTransactionsRequest {
+            since: Some(1),
+            until: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(ledger.list(ACTOR, &window).unwrap().transactions.len(), 5);
+
+        let bad = ListTransactionsRequest {
+            start: Some("next".to_string()),
+            ..Default::default()
+        };
+        assert!(ledger.list(ACTOR, &bad).is_err());
+    }
+}
@@ -1,8 +1,21 @@
-//! Fakepay - stub payments capability provider
+//! Fakepay - in-memory payments capability provider
 //!
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use tokio::sync::RwLock;
 use wasmbus_rpc::provider::prelude::*;
 use wasmcloud_examples_payments::*;
 
+mod ledger;
+mod scenario;
+use ledger::{now_millis, Ledger, DEFAULT_AUTH_TTL};
+use scenario::Scenarios;
+
+/// Link value naming the payment entity whose wallet `GetPaymentMethods` returns
+const ENTITY: &str = "entity";
+/// Link value with the number of seconds an auth code can be used
+const AUTH_TTL_SECS: &str = "auth_ttl_secs";
+
 // Start the provider and run until stopped by the host
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     provider_main(
@@ -16,11 +29,100 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// FakePay capability provider implementation
 #[derive(Default, Clone, Provider)]
 #[services(Payments)]
-struct FakePayProvider {}
+struct FakePayProvider {
+    /// Settings of each linked actor, keyed by actor id
+    links: Arc<RwLock<HashMap<String, LinkConfig>>>,
+    /// Wallets, authorizations, and payments, shared by all actors
+    ledger: Ledger,
+}
+
+/// Settings from a link's values
+#[derive(Clone, Debug)]
+struct LinkConfig {
+    /// The linked actor, which scopes its idempotency keys
+    actor_id: String,
+    /// Payment entity of the actor's wallet
+    entity: String,
+    /// How long auth codes issued to the actor can be used
+    auth_ttl: Duration,
+    /// Scripted outcomes of the actor's payments
+    scenarios: Scenarios,
+}
+
+impl LinkConfig {
+    fn from_link(ld: &LinkDefinition) -> Result<LinkConfig, String> {
+        let entity = ld
+            .values
+            .get(ENTITY)
+            .cloned()
+            .unwrap_or_else(|| ld.actor_id.clone());
+        let auth_ttl = match ld.values.get(AUTH_TTL_SECS) {
+            Some(secs) => Duration::from_secs(
+                secs.parse::<u64>()
+                    .map_err(|e| format!("invalid {AUTH_TTL_SECS} '{secs}': {e}"))?,
+            ),
+            None => DEFAULT_AUTH_TTL,
+        };
+        let scenarios = Scenarios::from_values(&ld.values)?;
+        Ok(LinkConfig {
+            actor_id: ld.actor_id.clone(),
+            entity,
+            auth_ttl,
+            scenarios,
+        })
+    }
+
+    /// Waits for the link's `latency_ms`, if set
+    async fn delay(&self) {
+        if let Some(latency) = self.scenarios.latency() {
+            tokio::time::sleep(latency).await;
+        }
+    }
+}
+
+impl FakePayProvider {
+    /// Returns the settings of the calling actor's link
+    async fn link(&self, ctx: &Context) -> RpcResult<LinkConfig> {
+        let actor_id = ctx
+            .actor
+            .as_ref()
+            .ok_or_else(|| RpcError::InvalidParameter("no actor in request".to_string()))?;
+        Ok(self
+            .links
+            .read()
+            .await
+            .get(actor_id)
+            .cloned()
+            .unwrap_or_else(|| LinkConfig {
+                actor_id: actor_id.clone(),
+                entity: actor_id.clone(),
+                auth_ttl: DEFAULT_AUTH_TTL,
+                scenarios: Scenarios::default(),
+            }))
+    }
+}
 
 /// use default implementations of provider message handlers
 impl ProviderDispatch for FakePayProvider {}
-impl ProviderHandler for FakePayProvider {}
+#[async_trait]
+impl ProviderHandler for FakePayProvider {
+    async fn put_link(&self, ld: &LinkDefinition) -> RpcResult<bool> {
+        let config = match LinkConfig::from_link(ld) {
+            Ok(config) => config,
+            Err(e) => {
+                // If the settings are invalid, deny link definition
+                log::error!("Rejecting link from actor {}: {}", &ld.actor_id, e);
+                return Ok(false);
+            }
+        };
+        self.links.write().await.insert(ld.actor_id.clone(), config);
+        Ok(true)
+    }
+
+    async fn delete_link(&self, actor_id: &str) {
+        self.links.write().await.remove(actor_id);
+    }
+}
 
 /// Handle FakePay methods
 #[async_trait]
@@ -31,21 +133,40 @@ impl Payments for FakePayProvider {
     /// a validation code (in other words, all payments have to be pre-authorized).
     async fn authorize_payment(
         &self,
-        _ctx: &Context,
-        _arg: &AuthorizePaymentRequest,
+        ctx: &Context,
+        arg: &AuthorizePaymentRequest,
     ) -> RpcResult<AuthorizePaymentResponse> {
-        todo!()
+        let link = self.link(ctx).await?;
+        link.delay().await;
+        let now = now_millis();
+        self.ledger.remove_expired(now);
+        let scenario = link.scenarios.find(arg);
+        if let Some(latency) = scenario.and_then(|s| s.latency) {
+            tokio::time::sleep(latency).await;
+        }
+        self.ledger.authorize(
+            &link.actor_id,
+            arg,
+            scenario.map(|s| s.outcome),
+            now,
+            link.auth_ttl,
+        )
     }
 
     /// Completes a previously authorized payment.
     /// This operation requires the "authorization code" from a successful
-    /// authorization operation.
+    /// authorization operation. Less than the authorized amount may be captured,
+    /// and the rest of the authorization is released.
     async fn complete_payment(
         &self,
-        _ctx: &Context,
-        _arg: &CompletePaymentRequest,
+        ctx: &Context,
+        arg: &CompletePaymentRequest,
     ) -> RpcResult<CompletePaymentResponse> {
-        todo!()
+        let link = self.link(ctx).await?;
+        link.delay().await;
+        let now = now_millis();
+        self.ledger.remove_expired(now);
+        self.ledger.complete(&link.actor_id, arg, now)
     }
 
     /// `GetPaymentMethods` - Retrieves an _opaque_ list of payment methods,
@@ -57,7 +178,57 @@ impl Payments for FakePayProvider {
     /// complete a payment transaction. A customer could have previously
     /// supplied their credit card and user-friendly labels for those methods
     /// like "personal" and "work", etc.
-    async fn get_payment_methods(&self, _ctx: &Context) -> RpcResult<PaymentMethods> {
-        todo!()
+    async fn get_payment_methods(&self, ctx: &Context) -> RpcResult<PaymentMethods> {
+        let link = self.link(ctx).await?;
+        link.delay().await;
+        Ok(self.ledger.payment_methods(&link.entity))
+    }
+
+    /// Returns a payment completed by the calling actor, with its refunds
+    async fn get_transaction<TS: ToString + ?Sized + std::marker::Sync>(
+        &self,
+        ctx: &Context,
+        arg: &TS,
+    ) -> RpcResult<GetTransactionResponse> {
+        let link = self.link(ctx).await?;
+        link.delay().await;
+        Ok(self.ledger.transaction(&link.actor_id, &arg.to_string()))
+    }
+
+    /// Lists payments completed by the calling actor, oldest first, for reconciliation
+    async fn list_transactions(
+        &self,
+        ctx: &Context,
+        arg: &ListTransactionsRequest,
+    ) -> RpcResult<ListTransactionsResponse> {
+        let link = self.link(ctx).await?;
+        link.delay().await;
+        self.ledger.list(&link.actor_id, arg)
+    }
+
+    /// Refunds all or part of a completed payment. The total refunded for a
+    /// payment can't exceed the amount captured by `CompletePayment`.
+    async fn refund_payment(
+        &self,
+        ctx: &Context,
+        arg: &RefundPaymentRequest,
+    ) -> RpcResult<RefundPaymentResponse> {
+        let link = self.link(ctx).await?;
+        link.delay().await;
+        self.ledger.refund(&link.actor_id, arg, now_millis())
+    }
+
+    /// Cancels an authorization that hasn't been completed, so that its
+    /// auth code can't be used to complete a payment.
+    async fn void_authorization(
+        &self,
+        ctx: &Context,
+        arg: &VoidAuthorizationRequest,
+    ) -> RpcResult<VoidAuthorizationResponse> {
+        let link = self.link(ctx).await?;
+        link.delay().await;
+        let now = now_millis();
+        self.ledger.remove_expired(now);
+        self.ledger.void(&link.actor_id, arg, now)
     }
 }
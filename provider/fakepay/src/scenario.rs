@@ -0,0 +1,219 @@
+//! Scripted outcomes for testing how actors handle payment failures
+//!
+//! Like the test card numbers of real payment processors, a link can map
+//! payment method tokens or amounts to outcomes with link values:
+//!
+//! - `scenario.token.<token>=<outcome>` applies to payments with the token.
+//!   Scenario tokens can be used by any payment entity.
+//! - `scenario.amount.<cents>=<outcome>` applies to payments of the amount
+//!   (before tax), unless the token has a scenario.
+//!
+//! The outcome may be followed by `:<milliseconds>` to delay the response,
+//! e.g., `decline:2000`. The `latency_ms` link value delays every response.
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use wasmcloud_examples_payments::AuthorizePaymentRequest;
+
+const TOKEN_PREFIX: &str = "scenario.token.";
+const AMOUNT_PREFIX: &str = "scenario.amount.";
+const LATENCY_MS: &str = "latency_ms";
+
+/// What happens to an authorization
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// Authorize the payment
+    Approve,
+    /// Decline the payment
+    Decline,
+    /// Decline the payment for insufficient funds
+    InsufficientFunds,
+    /// Authorize the payment with an auth code that has already expired
+    ExpiredAuth,
+    /// Decline the payment because its reference id was already used
+    DuplicateReference,
+}
+
+impl Outcome {
+    /// Returns the `fail_reason` of a declined authorization
+    pub fn fail_reason(&self) -> Option<&'static str> {
+        match self {
+            Outcome::Approve | Outcome::ExpiredAuth => None,
+            Outcome::Decline => Some("payment declined"),
+            Outcome::InsufficientFunds => Some("insufficient funds"),
+            Outcome::DuplicateReference => Some("duplicate reference id"),
+        }
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "approve" => Ok(Outcome::Approve),
+            "decline" => Ok(Outcome::Decline),
+            "insufficient_funds" => Ok(Outcome::InsufficientFunds),
+            "expired_auth" => Ok(Outcome::ExpiredAuth),
+            "duplicate_reference" => Ok(Outcome::DuplicateReference),
+            _ => Err(format!(
+                "unknown outcome '{s}', expected one of approve, decline, insufficient_funds, \
+                 expired_auth, duplicate_reference"
+            )),
+        }
+    }
+}
+
+/// An outcome, and how long to wait before responding
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scenario {
+    pub outcome: Outcome,
+    pub latency: Option<Duration>,
+}
+
+impl FromStr for Scenario {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (outcome, latency) = match s.split_once(':') {
+            Some((outcome, ms)) => (outcome, Some(parse_millis(ms)?)),
+            None => (s, None),
+        };
+        Ok(Scenario {
+            outcome: outcome.trim().parse()?,
+            latency,
+        })
+    }
+}
+
+/// The scenarios of a link
+#[derive(Clone, Debug, Default)]
+pub struct Scenarios {
+    tokens: HashMap<String, Scenario>,
+    amounts: HashMap<u32, Scenario>,
+    /// Delay of every response
+    latency: Option<Duration>,
+}
+
+impl Scenarios {
+    /// Reads the scenarios from link values
+    pub fn from_values(values: &HashMap<String, String>) -> Result<Scenarios, String> {
+        let mut scenarios = Scenarios::default();
+        for (key, value) in values.iter() {
+            let scenario = || {
+                value
+                    .parse::<Scenario>()
+                    .map_err(|e| format!("invalid {key}: {e}"))
+            };
+            if let Some(token) = key.strip_prefix(TOKEN_PREFIX) {
+                scenarios.tokens.insert(token.to_string(), scenario()?);
+            } else if let Some(amount) = key.strip_prefix(AMOUNT_PREFIX) {
+                let amount = amount
+                    .parse::<u32>()
+                    .map_err(|e| format!("invalid amount in {key}: {e}"))?;
+                scenarios.amounts.insert(amount, scenario()?);
+            } else if key == LATENCY_MS {
+                scenarios.latency = Some(parse_millis(value)?);
+            }
+        }
+        Ok(scenarios)
+    }
+
+    /// Returns the scenario for an authorization, if the token or amount has one
+    pub fn find(&self, req: &AuthorizePaymentRequest) -> Option<&Scenario> {
+        self.tokens
+            .get(&req.payment_method)
+            .or_else(|| self.amounts.get(&req.amount))
+    }
+
+    /// Returns the delay of every response
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+}
+
+fn parse_millis(ms: &str) -> Result<Duration, String> {
+    ms.trim()
+        .parse::<u64>()
+        .map(Duration::from_millis)
+        .map_err(|e| format!("invalid milliseconds '{ms}': {e}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn request(token: &str, amount: u32) -> AuthorizePaymentRequest {
+        AuthorizePaymentRequest {
+            amount,
+            payment_method: token.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_scenarios() {
+        let scenarios = Scenarios::from_values(&values(&[
+            ("scenario.token.tok_declined", "decline"),
+            ("scenario.token.tok_slow", "approve:1500"),
+            ("scenario.amount.9999", "insufficient_funds"),
+            ("latency_ms", "20"),
+            ("entity", "alice"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            scenarios.find(&request("tok_declined", 100)),
+            Some(&Scenario {
+                outcome: Outcome::Decline,
+                latency: None
+            })
+        );
+        assert_eq!(
+            scenarios.find(&request("tok_slow", 100)),
+            Some(&Scenario {
+                outcome: Outcome::Approve,
+                latency: Some(Duration::from_millis(1500))
+            })
+        );
+        assert_eq!(
+            scenarios.find(&request("pm_1", 9999)).map(|s| s.outcome),
+            Some(Outcome::InsufficientFunds)
+        );
+        assert_eq!(scenarios.find(&request("pm_1", 100)), None);
+        assert_eq!(scenarios.latency(), Some(Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn token_scenario_overrides_amount() {
+        let scenarios = Scenarios::from_values(&values(&[
+            ("scenario.token.tok_ok", "approve"),
+            ("scenario.amount.500", "duplicate_reference"),
+        ]))
+        .unwrap();
+        let found = scenarios.find(&request("tok_ok", 500)).unwrap();
+        assert_eq!(found.outcome, Outcome::Approve);
+        let found = scenarios.find(&request("pm_1", 500)).unwrap();
+        assert_eq!(found.outcome.fail_reason(), Some("duplicate reference id"));
+    }
+
+    #[test]
+    fn invalid_scenarios() {
+        for (key, value) in [
+            ("scenario.token.tok", "explode"),
+            ("scenario.token.tok", "decline:soon"),
+            ("scenario.amount.lots", "decline"),
+            ("latency_ms", "-1"),
+        ] {
+            assert!(
+                Scenarios::from_values(&values(&[(key, value)])).is_err(),
+                "{key}={value}"
+            );
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::time::{Duration, Instant};
+
+use wasmbus_rpc::provider::prelude::*;
+use wasmcloud_examples_payments::*;
+use wasmcloud_test_util::{
+    check, check_eq,
+    cli::print_test_results,
+    provider_test::test_provider,
+    testing::{TestOptions, TestResult},
+};
+#[allow(unused_imports)]
+use wasmcloud_test_util::{run_selected, run_selected_spawn};
+
+#[tokio::test]
+async fn run_all() {
+    let opts = TestOptions::default();
+    let res = run_selected_spawn!(
+        &opts,
+        health_check,
+        pay_from_wallet,
+        auth_code_used_once,
+        scripted_declines,
+        scripted_expired_auth,
+        scripted_latency,
+        void_and_refund,
+        idempotent_complete,
+        transaction_lookup
+    );
+    print_test_results(&res);
+
+    let passed = res.iter().filter(|tr| tr.passed).count();
+    let total = res.len();
+    assert_eq!(passed, total, "{} passed out of {}", passed, total);
+
+    // try to let the provider shut down gracefully
+    let provider = test_provider().await;
+    let _ = provider.shutdown().await;
+}
+
+/// test that health check returns healthy
+async fn health_check(_opt: &TestOptions) -> RpcResult<()> {
+    let prov = test_provider().await;
+
+    // health check
+    let hc = prov.health_check().await;
+    check!(hc.is_ok())?;
+    Ok(())
+}
+
+/// authorization request for the test link's entity
+fn authorize_request(token: &str, amount: u32) -> AuthorizePaymentRequest {
+    AuthorizePaymentRequest {
+        amount,
+        tax: 50,
+        payment_method: token.to_string(),
+        payment_entity: "test-customer".to_string(),
+        reference_id: "order-1".to_string(),
+        idempotency_key: None,
+    }
+}
+
+fn complete_request(auth_code: &str) -> CompletePaymentRequest {
+    CompletePaymentRequest {
+        auth_code: auth_code.to_string(),
+        description: Some("test payment".to_string()),
+        amount: None,
+        idempotency_key: None,
+    }
+}
+
+/// pay with a method from the wallet
+async fn pay_from_wallet(_opt: &TestOptions) -> RpcResult<()> {
+    let prov = test_provider().await;
+    let client = PaymentsSender::via(prov);
+    let ctx = Context::default();
+
+    let methods = client.get_payment_methods(&ctx).await?;
+    check!(!methods.is_empty())?;
+    let token = methods[0].token.clone().unwrap_or_default();
+
+    let auth = client
+        .authorize_payment(&ctx, &authorize_request(&token, 1000))
+        .await?;
+    check!(auth.success)?;
+    let auth_code = auth.auth_code.unwrap_or_default();
+
+    let done = client
+        .complete_payment(&ctx, &complete_request(&auth_code))
+        .await?;
+    check!(done.success)?;
+    check!(!done.txid.is_empty())?;
+    check!(done.timestamp > 0)?;
+    Ok(())
+}
+
+/// an auth code can't be used twice
+async fn auth_code_used_once(_opt: &TestOptions) -> RpcResult<()> {
+    let prov = test_provider().await;
+    let client = PaymentsSender::via(prov);
+    let ctx = Context::default();
+
+    let methods = client.get_payment_methods(&ctx).await?;
+    let token = methods[0].token.clone().unwrap_or_default();
+    let auth = client
+        .authorize_payment(&ctx, &authorize_request(&token, 1000))
+        .await?;
+    let auth_code = auth.auth_code.unwrap_or_default();
+    client
+        .complete_payment(&ctx, &complete_request(&auth_code))
+        .await?;
+    let again = client
+        .complete_payment(&ctx, &complete_request(&auth_code))
+        .await;
+    check!(again.is_err())?;
+    Ok(())
+}
+
+/// tokens and amounts in the link values are declined
+async fn scripted_declines(_opt: &TestOptions) -> RpcResult<()> {
+    let prov = test_provider().await;
+    let client = PaymentsSender::via(prov);
+    let ctx = Context::default();
+
+    let declined = client
+        .authorize_payment(&ctx, &authorize_request("tok_declined", 1000))
+        .await?;
+    check!(!declined.success)?;
+    check_eq!(declined.fail_reason.as_deref(), Some("payment declined"))?;
+
+    let methods = client.get_payment_methods(&ctx).await?;
+    let token = methods[0].token.clone().unwrap_or_default();
+    let poor = client
+        .authorize_payment(&ctx, &authorize_request(&token, 9999))
+        .await?;
+    check!(!poor.success)?;
+    check_eq!(poor.fail_reason.as_deref(), Some("insufficient funds"))?;
+
+    let duplicate = client
+        .authorize_payment(&ctx, &authorize_request(&token, 4242))
+        .await?;
+    check!(!duplicate.success)?;
+    check_eq!(
+        duplicate.fail_reason.as_deref(),
+        Some("duplicate reference id")
+    )?;
+    Ok(())
+}
+
+/// a scripted auth code expires before it can be used
+async fn scripted_expired_auth(_opt: &TestOptions) -> RpcResult<()> {
+    let prov = test_provider().await;
+    let client = PaymentsSender::via(prov);
+    let ctx = Context::default();
+
+    let auth = client
+        .authorize_payment(&ctx, &authorize_request("tok_expired", 1000))
+        .await?;
+    check!(auth.success)?;
+    let auth_code = auth.auth_code.unwrap_or_default();
+    let done = client
+        .complete_payment(&ctx, &complete_request(&auth_code))
+        .await;
+    check!(done.is_err())?;
+    Ok(())
+}
+
+/// a scripted token delays the response
+async fn scripted_latency(_opt: &TestOptions) -> RpcResult<()> {
+    let prov = test_provider().await;
+    let client = PaymentsSender::via(prov);
+    let ctx = Context::default();
+
+    let start = Instant::now();
+    let auth = client
+        .authorize_payment(&ctx, &authorize_request("tok_slow", 1000))
+        .await?;
+    check!(auth.success)?;
+    check!(start.elapsed() >= Duration::from_millis(500))?;
+    Ok(())
+}
+
+/// voided authorizations can't be completed, and refunds can't exceed the captured amount
+async fn void_and_refund(_opt: &TestOptions) -> RpcResult<()> {
+    let prov = test_provider().await;
+    let client = PaymentsSender::via(prov);
+    let ctx = Context::default();
+
+    let methods = client.get_payment_methods(&ctx).await?;
+    let token = methods[0].token.clone().unwrap_or_default();
+
+    let auth = client
+        .authorize_payment(&ctx, &authorize_request(&token, 1000))
+        .await?;
+    let auth_code = auth.auth_code.unwrap_or_default();
+    let void = client
+        .void_authorization(
+            &ctx,
+            &VoidAuthorizationRequest {
+                auth_code: auth_code.clone(),
+                idempotency_key: None,
+            },
+        )
+        .await?;
+    check!(void.success)?;
+    let done = client
+        .complete_payment(&ctx, &complete_request(&auth_code))
+        .await;
+    check!(done.is_err())?;
+
+    // capture part of the authorized 1050
+    let auth = client
+        .authorize_payment(&ctx, &authorize_request(&token, 1000))
+        .await?;
+    let mut capture = complete_request(&auth.auth_code.unwrap_or_default());
+    capture.amount = Some(800);
+    let done = client.complete_payment(&ctx, &capture).await?;
+    check_eq!(done.amount, Some(800))?;
+
+    let refund = |amount| RefundPaymentRequest {
+        txid: done.txid.clone(),
+        amount,
+        reason: Some("test refund".to_string()),
+        idempotency_key: None,
+    };
+    let partial = client.refund_payment(&ctx, &refund(Some(300))).await?;
+    check!(partial.success)?;
+    check_eq!(partial.refunded_amount, 300)?;
+    let too_much = client.refund_payment(&ctx, &refund(Some(501))).await?;
+    check!(!too_much.success)?;
+    let rest = client.refund_payment(&ctx, &refund(None)).await?;
+    check!(rest.success)?;
+    check_eq!(rest.refunded_amount, 800)?;
+    Ok(())
+}
+
+/// a retried CompletePayment with the same idempotency key doesn't capture twice
+async fn idempotent_complete(_opt: &TestOptions) -> RpcResult<()> {
+    let prov = test_provider().await;
+    let client = PaymentsSender::via(prov);
+    let ctx = Context::default();
+
+    let methods = client.get_payment_methods(&ctx).await?;
+    let token = methods[0].token.clone().unwrap_or_default();
+    let auth = client
+        .authorize_payment(&ctx, &authorize_request(&token, 1000))
+        .await?;
+    let mut req = complete_request(&auth.auth_code.unwrap_or_default());
+    req.idempotency_key = Some("complete-once".to_string());
+    let done = client.complete_payment(&ctx, &req).await?;
+    let again = client.complete_payment(&ctx, &req).await?;
+    check_eq!(again, done)?;
+
+    // the key can't be reused for a different request
+    req.amount = Some(100);
+    let other = client.complete_payment(&ctx, &req).await;
+    check!(other.is_err())?;
+    Ok(())
+}
+
+/// completed payments can be looked up and listed
+async fn transaction_lookup(_opt: &TestOptions) -> RpcResult<()> {
+    let prov = test_provider().await;
+    let client = PaymentsSender::via(prov);
+    let ctx = Context::default();
+
+    let methods = client.get_payment_methods(&ctx).await?;
+    let token = methods[0].token.clone().unwrap_or_default();
+    let mut auth_req = authorize_request(&token, 1000);
+    auth_req.reference_id = "order-lookup".to_string();
+    let auth = client.authorize_payment(&ctx, &auth_req).await?;
+    let done = client
+        .complete_payment(&ctx, &complete_request(&auth.auth_code.unwrap_or_default()))
+        .await?;
+
+    let found = client.get_transaction(&ctx, &done.txid).await?;
+    check!(found.found)?;
+    let tx = found.transaction.unwrap_or_default();
+    check_eq!(tx.txid, done.txid)?;
+    check_eq!(tx.captured_amount, 1050)?;
+    check_eq!(tx.reference_id.as_str(), "order-lookup")?;
+
+    let missing = client.get_transaction(&ctx, "tx_unknown").await?;
+    check!(!missing.found)?;
+
+    let listed = client
+        .list_transactions(
+            &ctx,
+            &ListTransactionsRequest {
+                reference_id: Some("order-lookup".to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+    check_eq!(listed.transactions.len(), 1)?;
+    check_eq!(&listed.transactions[0].txid, &done.txid)?;
+    Ok(())
+}
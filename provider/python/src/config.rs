@@ -3,7 +3,7 @@
 //!
 use log::debug;
 use serde::Deserialize;
-use std::{collections::HashMap, env, path::PathBuf};
+use std::{collections::HashMap, env, path::PathBuf, time::Duration};
 use wasmbus_rpc::error::{RpcError, RpcResult};
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -18,6 +18,7 @@ pub struct Config {
     pub venv_path: Option<String>,
 
     /// sys.path - where to load other libraries: venv PYTHON_PATH
+    #[serde(default)]
     pub sys_path: Vec<String>,
 
     /// max size of thread pool
@@ -25,10 +26,57 @@ pub struct Config {
 
     /// min/initial size of thread pool
     pub min_threads: Option<u8>,
+
+    /// smithy model (JSON AST) to check parameters and results against: env PYTHON_SCHEMA
+    pub schema_path: Option<String>,
+
+    /// method of the calling actor that receives chunks of generator results
+    pub stream_method: Option<String>,
+
+    /// maximum number of items in each chunk of a generator result
+    pub stream_chunk_size: Option<u32>,
+
+    /// number of python worker processes: env PYTHON_WORKERS.
+    /// If not set, python runs in the provider's process.
+    pub workers: Option<u8>,
+
+    /// address to serve prometheus metrics on: env PYTHON_METRICS_ADDR
+    pub metrics_addr: Option<String>,
+
+    /// how long to wait for the python engine to start, in milliseconds:
+    /// env PYTHON_STARTUP_TIMEOUT_MS
+    pub startup_timeout_ms: Option<u64>,
+
+    /// timeout for python calls, in milliseconds: env PYTHON_TIMEOUT_MS
+    pub timeout_ms: Option<u64>,
+
+    /// timeouts of individual methods, in milliseconds, overriding timeout_ms
+    #[serde(default)]
+    pub method_timeouts_ms: HashMap<String, u64>,
+
+    /// methods that run exclusively, in the python engine's main thread.
+    /// Other methods run in the engine's thread pool.
+    #[serde(default)]
+    pub exclusive_methods: Vec<String>,
 }
 
 impl Config {
-    /// initialize from linkdef values, override from environment
+    /// timeout for a method, if it has one
+    pub fn method_timeout(&self, method: &str) -> Option<Duration> {
+        self.method_timeouts_ms
+            .get(method)
+            .copied()
+            .or(self.timeout_ms)
+            .map(Duration::from_millis)
+    }
+
+    /// whether a method is configured to run exclusively
+    pub fn is_exclusive(&self, method: &str) -> bool {
+        self.exclusive_methods.iter().any(|m| m == method)
+    }
+
+    /// initialize from linkdef values, with defaults from environment.
+    /// A link that sets its own python_main doesn't use PYTHON_MAIN or PYTHON_DISPATCH.
     pub fn init(values: HashMap<String, String>) -> RpcResult<Config> {
         let mut config = if let Some(config_b64) = values.get("config_b64") {
             let bytes = base64::decode(config_b64.as_bytes()).map_err(|e| {
@@ -42,13 +90,17 @@ impl Config {
         } else {
             Config::default()
         };
-        if let Ok(prog) = env::var("PYTHON_MAIN") {
-            debug!("using python_main: {}", &prog);
-            config.python_main = Some(prog);
-        }
-        if let Ok(prog) = env::var("PYTHON_DISPATCH") {
-            debug!("using python_dispatch: {}", &prog);
-            config.python_dispatch = Some(prog);
+        if config.python_main.is_none() {
+            if let Ok(prog) = env::var("PYTHON_MAIN") {
+                debug!("using python_main: {}", &prog);
+                config.python_main = Some(prog);
+            }
+            if config.python_dispatch.is_none() {
+                if let Ok(prog) = env::var("PYTHON_DISPATCH") {
+                    debug!("using python_dispatch: {}", &prog);
+                    config.python_dispatch = Some(prog);
+                }
+            }
         }
         if let Ok(syspath) = env::var("PYTHON_PATH") {
             if !syspath.is_empty() {
@@ -58,9 +110,49 @@ impl Config {
                 }
             }
         }
-        if let Ok(venv) = env::var("VENV_PATH") {
-            debug!("using venv: {}", &venv);
-            config.venv_path = Some(venv);
+        if config.venv_path.is_none() {
+            if let Ok(venv) = env::var("VENV_PATH") {
+                debug!("using venv: {}", &venv);
+                config.venv_path = Some(venv);
+            }
+        }
+        if config.schema_path.is_none() {
+            if let Ok(schema) = env::var("PYTHON_SCHEMA") {
+                debug!("using schema: {}", &schema);
+                config.schema_path = Some(schema);
+            }
+        }
+        if config.workers.is_none() {
+            if let Ok(workers) = env::var("PYTHON_WORKERS") {
+                let workers = workers.parse::<u8>().map_err(|e| {
+                    RpcError::ProviderInit(format!("invalid PYTHON_WORKERS: {}", e))
+                })?;
+                debug!("using workers: {}", workers);
+                config.workers = Some(workers);
+            }
+        }
+        if config.metrics_addr.is_none() {
+            if let Ok(addr) = env::var("PYTHON_METRICS_ADDR") {
+                debug!("using metrics_addr: {}", &addr);
+                config.metrics_addr = Some(addr);
+            }
+        }
+        if config.startup_timeout_ms.is_none() {
+            if let Ok(timeout) = env::var("PYTHON_STARTUP_TIMEOUT_MS") {
+                let timeout = timeout.parse::<u64>().map_err(|e| {
+                    RpcError::ProviderInit(format!("invalid PYTHON_STARTUP_TIMEOUT_MS: {}", e))
+                })?;
+                config.startup_timeout_ms = Some(timeout);
+            }
+        }
+        if config.timeout_ms.is_none() {
+            if let Ok(timeout) = env::var("PYTHON_TIMEOUT_MS") {
+                let timeout = timeout.parse::<u64>().map_err(|e| {
+                    RpcError::ProviderInit(format!("invalid PYTHON_TIMEOUT_MS: {}", e))
+                })?;
+                debug!("using timeout_ms: {}", timeout);
+                config.timeout_ms = Some(timeout);
+            }
         }
         if let Some(venv) = &config.venv_path {
             if !PathBuf::from(&venv).is_dir() {
@@ -0,0 +1,57 @@
+//! The python engine that runs tasks: the in-process pime engine (the default),
+//! or a pool of worker subprocesses if `workers` is configured
+
+use serde_value::Value;
+
+use crate::{
+    pime::{self, Error, PyTask},
+    worker,
+};
+
+/// Run a task and return its result
+pub(crate) async fn call(task: PyTask) -> Result<Option<Value>, Error> {
+    if worker::is_started().await {
+        worker::call(task).await
+    } else {
+        pime::call(task).await
+    }
+}
+
+/// Whether either engine is running
+pub(crate) async fn is_started() -> bool {
+    worker::is_started().await || pime::is_engine_started()
+}
+
+/// Describes the engine's state, for health checks
+pub(crate) async fn state() -> &'static str {
+    if worker::is_started().await {
+        return "workers";
+    }
+    match pime::get_engine_state() {
+        pime::STATE_STARTED => "started",
+        pime::STATE_STOPPING => "stopping",
+        _ => "stopped",
+    }
+}
+
+/// Stop the engine, if it is running
+pub(crate) async fn stop() -> Result<(), Error> {
+    if worker::is_started().await {
+        worker::stop().await;
+        return Ok(());
+    }
+    if pime::is_engine_started() {
+        pime::stop().await?;
+    }
+    Ok(())
+}
+
+/// Returns the number of tasks that haven't returned
+pub(crate) async fn pending_tasks() -> usize {
+    worker::pending_tasks().await + pime::pending_tasks().await
+}
+
+/// Returns the number of tasks waiting to be picked up
+pub(crate) async fn queued_tasks() -> usize {
+    worker::queued_tasks().await + pime::queued_tasks().await
+}
@@ -0,0 +1,188 @@
+//! The `wasmcloud` python module, for python code to call back into the lattice
+//!
+//! ```python
+//! import wasmcloud
+//!
+//! def handle(command, arg):
+//!     wasmcloud.info("checking stock for {}".format(arg))
+//!     return wasmcloud.call_actor("MBCFOPM6...", "Inventory.Check", arg)
+//! ```
+//!
+//! Actors can only be called if they are linked to the provider.
+//! Python functions block while the call is in progress, with the GIL released.
+
+use lazy_static::lazy_static;
+use log::{log, Level};
+use pyo3::{exceptions::PyRuntimeError, prelude::*, types::PyModule};
+use pythonize::{depythonize, pythonize};
+use serde_value::Value;
+use std::{borrow::Cow, collections::HashMap, str::FromStr};
+use tokio::{runtime::Handle, sync::RwLock};
+use wasmbus_rpc::{
+    common::{deserialize, serialize, Context, Message, Transport},
+    core::LinkDefinition,
+    provider::ProviderTransport,
+};
+
+use crate::Service;
+
+/// name of the module in python's sys.modules
+const MODULE_NAME: &str = "wasmcloud";
+/// log target for messages logged from python
+const LOG_TARGET: &str = "python";
+
+lazy_static! {
+    /// links of actors that python may call, by actor id
+    static ref ACTOR_LINKS: RwLock<HashMap<String, ActorLink>> = RwLock::new(HashMap::new());
+    /// runtime used to send messages from python threads
+    static ref RUNTIME: std::sync::RwLock<Option<Handle>> = std::sync::RwLock::new(None);
+}
+
+/// An actor's link to the provider
+#[derive(Clone)]
+pub struct ActorLink {
+    pub ld: LinkDefinition,
+    /// service for the link's own python_main, if it has one
+    pub service: Option<Service>,
+}
+
+/// allow python to call an actor, and route the actor's invocations
+/// to `service`, if the link has its own python_main
+pub async fn put_link(ld: &LinkDefinition, service: Option<Service>) {
+    let link = ActorLink {
+        ld: ld.clone(),
+        service,
+    };
+    ACTOR_LINKS.write().await.insert(ld.actor_id.clone(), link);
+}
+
+/// stop python from calling an actor
+pub async fn delete_link(actor_id: &str) {
+    ACTOR_LINKS.write().await.remove(actor_id);
+}
+
+/// Returns an actor's link, if it is linked
+pub async fn get_link(actor_id: &str) -> Option<ActorLink> {
+    ACTOR_LINKS.read().await.get(actor_id).cloned()
+}
+
+/// Returns the service of any link that has its own python_main
+pub async fn any_link_service() -> Option<Service> {
+    ACTOR_LINKS
+        .read()
+        .await
+        .values()
+        .find_map(|link| link.service.clone())
+}
+
+/// add the wasmcloud module to python. Must be called from the runtime
+/// that will send messages for python.
+pub(crate) fn add_module(py: Python) -> PyResult<()> {
+    *RUNTIME.write().unwrap() = Some(Handle::current());
+    let module = PyModule::new(py, MODULE_NAME)?;
+    module.add_function(wrap_pyfunction!(call_actor, module)?)?;
+    module.add_function(wrap_pyfunction!(log_message, module)?)?;
+    module.add_function(wrap_pyfunction!(debug, module)?)?;
+    module.add_function(wrap_pyfunction!(info, module)?)?;
+    module.add_function(wrap_pyfunction!(warn, module)?)?;
+    module.add_function(wrap_pyfunction!(error, module)?)?;
+    py.import("sys")?
+        .getattr("modules")?
+        .set_item(MODULE_NAME, module)?;
+    Ok(())
+}
+
+/// Invoke a method on a linked actor, and return its result.
+/// `arg` and the result are converted like the provider's own parameters.
+#[pyfunction]
+fn call_actor(
+    py: Python,
+    actor_id: String,
+    method: String,
+    arg: Option<&PyAny>,
+) -> PyResult<Py<PyAny>> {
+    let value: Option<Value> = match arg {
+        Some(arg) => Some(depythonize(arg).map_err(|e| PyRuntimeError::new_err(e.to_string()))?),
+        None => None,
+    };
+    let handle = RUNTIME
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or_else(|| PyRuntimeError::new_err("wasmcloud module is not initialized"))?;
+    let result = py.allow_threads(|| handle.block_on(send(&actor_id, &method, value)));
+    match result {
+        Ok(value) => Ok(pythonize(py, &value)?),
+        Err(e) => Err(PyRuntimeError::new_err(format!(
+            "calling {} on actor {}: {}",
+            &method, &actor_id, e
+        ))),
+    }
+}
+
+/// whether an actor is linked, so python can call it
+pub(crate) async fn is_linked(actor_id: &str) -> bool {
+    ACTOR_LINKS.read().await.contains_key(actor_id)
+}
+
+/// send a message to a linked actor, and return its result
+pub(crate) async fn send(
+    actor_id: &str,
+    method: &str,
+    arg: Option<Value>,
+) -> Result<Value, String> {
+    let ld = get_link(actor_id)
+        .await
+        .map(|link| link.ld)
+        .ok_or_else(|| "actor is not linked to the python provider".to_string())?;
+    let buf = match arg {
+        Some(arg) => serialize(&arg).map_err(|e| e.to_string())?,
+        None => Vec::new(),
+    };
+    let transport = ProviderTransport::new(&ld, None);
+    let resp = transport
+        .send(
+            &Context::default(),
+            Message {
+                method,
+                arg: Cow::Borrowed(&buf),
+            },
+            None,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.is_empty() {
+        return Ok(Value::Option(None));
+    }
+    deserialize(&resp).map_err(|e| e.to_string())
+}
+
+/// Log a message at a level ("error", "warn", "info", "debug", or "trace")
+#[pyfunction]
+#[pyo3(name = "log")]
+fn log_message(level: &str, message: &str) -> PyResult<()> {
+    let level = Level::from_str(level)
+        .map_err(|_| PyRuntimeError::new_err(format!("invalid log level '{}'", level)))?;
+    log!(target: LOG_TARGET, level, "{}", message);
+    Ok(())
+}
+
+#[pyfunction]
+fn debug(message: &str) {
+    log!(target: LOG_TARGET, Level::Debug, "{}", message);
+}
+
+#[pyfunction]
+fn info(message: &str) {
+    log!(target: LOG_TARGET, Level::Info, "{}", message);
+}
+
+#[pyfunction]
+fn warn(message: &str) {
+    log!(target: LOG_TARGET, Level::Warn, "{}", message);
+}
+
+#[pyfunction]
+fn error(message: &str) {
+    log!(target: LOG_TARGET, Level::Error, "{}", message);
+}
@@ -1,10 +1,12 @@
 //! Rust-Python integration:
 //! - initialize python GIL
+//! - load the python_main module of each link into the shared engine
 //! - invoke python methods
-//! - auto-reload when changes are detected
+//! - auto-reload when changes are detected (see watch.rs)
 
-use log::{debug, error, info};
-use pyo3::Python;
+use lazy_static::lazy_static;
+use log::{debug, error};
+use pyo3::{types::PyModule, Python};
 use serde_value::Value;
 use std::{
     collections::{BTreeMap, HashMap},
@@ -12,54 +14,254 @@ use std::{
     path::PathBuf,
     str::FromStr,
     sync::Arc,
-    time::SystemTime,
+    time::{Duration, Instant},
 };
-use tokio::sync::RwLock;
-use wasmbus_rpc::provider::prelude::*;
+use wasmbus_rpc::{core::HealthCheckResponse, provider::prelude::*};
 
 mod config;
 pub use config::Config;
+mod engine;
+pub mod host;
+mod metrics;
 pub(crate) mod pime;
+mod schema;
+mod stream;
+mod watch;
+mod worker;
 
 const THREAD_POOL_MIN_THREADS: u8 = 4;
 const THREAD_POOL_MAX_THREADS: u8 = 12;
+/// start of the error message for python exceptions
+const EXCEPTION_RAISED: &str = "Exception raised";
+/// how long to wait for the python engine to start, if not configured
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// python source of the engine's broker, which routes tasks to python_main modules
+const ROUTER_SOURCE: &str = include_str!("router.py");
+/// broker command that loads a python_main module
+const LOAD_COMMAND: &str = "__load__";
+/// task parameter naming the python_main module a task is for
+const MAIN_PARAM: &str = "__main__";
+/// broker command that checks the engine can run tasks
+const HEALTH_COMMAND: &str = "__health__";
+/// how long the health check waits for the engine
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+/// actor method that receives chunks of generator results, if not configured
+const STREAM_METHOD: &str = "PythonStream.Chunk";
+/// maximum number of items in each chunk of a generator result, if not configured
+const STREAM_CHUNK_SIZE: u32 = 16;
+
+lazy_static! {
+    /// settings of the running engine, locked while the engine starts
+    static ref ENGINE: tokio::sync::Mutex<Option<EngineSettings>> =
+        tokio::sync::Mutex::new(None);
+}
+
+/// Settings that configure the engine, which can't change once it is running
+#[derive(Clone, Debug)]
+struct EngineSettings {
+    venv_path: Option<String>,
+    workers: Option<u8>,
+    min_threads: Option<u8>,
+    max_threads: Option<u8>,
+    metrics_addr: Option<String>,
+}
+
+impl EngineSettings {
+    fn new(config: &Config) -> Self {
+        EngineSettings {
+            venv_path: config.venv_path.clone(),
+            workers: config.workers,
+            min_threads: config.min_threads,
+            max_threads: config.max_threads,
+            metrics_addr: config.metrics_addr.clone(),
+        }
+    }
+
+    /// names of the settings that a service sets differently than the running engine.
+    /// Settings the service doesn't set are the running engine's.
+    fn differences(&self, service: &EngineSettings) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        if service.venv_path.is_some() && service.venv_path != self.venv_path {
+            names.push("venv_path");
+        }
+        let workers = |s: &EngineSettings| s.workers.unwrap_or_default();
+        if service.workers.is_some() && workers(service) != workers(self) {
+            names.push("workers");
+        }
+        let min_threads = |s: &EngineSettings| s.min_threads.unwrap_or(THREAD_POOL_MIN_THREADS);
+        if service.min_threads.is_some() && min_threads(service) != min_threads(self) {
+            names.push("min_threads");
+        }
+        let max_threads = |s: &EngineSettings| s.max_threads.unwrap_or(THREAD_POOL_MAX_THREADS);
+        if service.max_threads.is_some() && max_threads(service) != max_threads(self) {
+            names.push("max_threads");
+        }
+        if service.metrics_addr.is_some() && service.metrics_addr != self.metrics_addr {
+            names.push("metrics_addr");
+        }
+        names
+    }
+}
+
+/// Who an invocation is from, passed to python in the task params
+/// `actor`, `link_name` and `trace_context`
+#[derive(Clone, Debug, Default)]
+pub struct CallContext {
+    /// public key of the calling actor
+    pub actor: Option<String>,
+    /// name of the actor's link to this provider
+    pub link_name: Option<String>,
+    /// tracing headers (e.g., `traceparent`) for continuing the caller's trace
+    pub trace_context: HashMap<String, String>,
+}
+
+impl CallContext {
+    /// add caller fields to task params
+    fn add_params(&self, params: &mut BTreeMap<String, Value>) {
+        let optional =
+            |s: &Option<String>| Value::Option(s.clone().map(|s| Box::new(Value::String(s))));
+        params.insert("actor".to_string(), optional(&self.actor));
+        params.insert("link_name".to_string(), optional(&self.link_name));
+        params.insert(
+            "trace_context".to_string(),
+            Value::Map(
+                self.trace_context
+                    .iter()
+                    .map(|(k, v)| (Value::String(k.clone()), Value::String(v.clone())))
+                    .collect(),
+            ),
+        );
+    }
+}
 
 #[derive(Clone, Debug, Default)]
 pub struct InstanceData {
     config: Config,
-    modified: Arc<RwLock<Option<SystemTime>>>,
+    /// canonical path of python_main, which identifies the module in the engine
+    main_path: String,
+    /// the module in the engine, which is watched for changes
+    module: Option<Arc<watch::Module>>,
+    /// model to check parameters and results against
+    schema: Option<Arc<schema::Schema>>,
 }
 
+/// A python_main module loaded into the engine. The engine is shared by all services,
+/// because of the python GIL, but each link may have its own service and module.
 #[derive(Clone, Default)]
 pub struct Service(pub InstanceData);
 
 impl Service {
+    /// Initialize a service from link values (or environment, if `vars` is None),
+    /// starting the python engine if this is the first service.
     pub async fn try_init(vars: Option<HashMap<String, String>>) -> RpcResult<Self> {
         let vars = vars.unwrap_or_default();
 
         let config = Config::init(vars)?;
-        let service = Service(InstanceData {
+        let main_path = std::fs::canonicalize(config.python_main.as_ref().unwrap())
+            .map_err(|e| RpcError::ProviderInit(format!("invalid python_main: {}", e)))?
+            .to_string_lossy()
+            .to_string();
+        let schema = match &config.schema_path {
+            Some(path) => Some(Arc::new(
+                schema::Schema::load(path).map_err(RpcError::ProviderInit)?,
+            )),
+            None => None,
+        };
+        let mut service = Service(InstanceData {
             config,
-            modified: Arc::new(RwLock::new(None)),
+            main_path,
+            module: None,
+            schema,
         });
 
-        if !pime::is_engine_started() {
+        service
+            .check_files()
+            .await
+            .map_err(|e| RpcError::ProviderInit(e.to_string()))?;
+        service.start_engine().await?;
+        let module = watch::Module::get(
+            &service.0.main_path,
+            &service.0.config.sys_path,
+            service.0.config.python_dispatch.as_deref(),
+        );
+        {
+            let _guard = module.enter().await;
             service
-                .init_python()
+                .load_module()
                 .await
                 .map_err(|e| RpcError::ProviderInit(e.to_string()))?;
         }
+        service.0.module = Some(module);
         Ok(service)
     }
 
+    /// Check that the engine is running and can run tasks in its thread pool
+    pub async fn health(&self) -> HealthCheckResponse {
+        let threads = match self.0.config.workers {
+            Some(workers) if workers > 0 => format!("workers={}", workers),
+            _ => format!(
+                "thread_pool={}-{}",
+                self.0.config.min_threads.unwrap_or(THREAD_POOL_MIN_THREADS),
+                self.0.config.max_threads.unwrap_or(THREAD_POOL_MAX_THREADS)
+            ),
+        };
+        let mut message = format!(
+            "engine={} tasks={} {}",
+            engine::state().await,
+            engine::pending_tasks().await,
+            threads
+        );
+        if !engine::is_started().await {
+            return HealthCheckResponse {
+                healthy: false,
+                message: Some(message),
+            };
+        }
+        let mut task = pime::PyTask::new0(Value::String(HEALTH_COMMAND.to_string()));
+        task.set_timeout(HEALTH_TIMEOUT);
+        let healthy = match engine::call(task).await {
+            Ok(Some(Value::Map(info))) => {
+                for (key, value) in info.iter() {
+                    // worker processes return the smallest integer type that fits
+                    let value = match value {
+                        Value::U8(n) => n.to_string(),
+                        Value::U16(n) => n.to_string(),
+                        Value::U32(n) => n.to_string(),
+                        Value::U64(n) => n.to_string(),
+                        Value::I8(n) => n.to_string(),
+                        Value::I16(n) => n.to_string(),
+                        Value::I32(n) => n.to_string(),
+                        Value::I64(n) => n.to_string(),
+                        _ => continue,
+                    };
+                    if let Value::String(key) = key {
+                        message.push_str(&format!(" python_{}={}", key, value));
+                    }
+                }
+                true
+            }
+            Ok(_) => true,
+            Err(e) => {
+                message.push_str(&format!(" error={}", e));
+                false
+            }
+        };
+        HealthCheckResponse {
+            healthy,
+            message: Some(message),
+        }
+    }
+
     /// stop the instance
     pub async fn shutdown() {
-        if let Err(e) = pime::stop().await {
+        if let Err(e) = engine::stop().await {
             error!("shutdown error: {}", e);
         }
     }
 
-    async fn init_python(&self) -> Result<(), pime::Error> {
+    /// check the configured python files
+    async fn check_files(&self) -> Result<(), pime::Error> {
         let py_main = PathBuf::from(&self.0.config.python_main.as_ref().unwrap());
         if py_main.is_dir() {
             if !py_main.join("__init__.py").is_file() {
@@ -95,15 +297,65 @@ impl Service {
                     ),
                 ));
             }
-            if let Ok(md) = std::fs::metadata(&py_disp) {
-                if let Ok(modified) = md.modified() {
-                    let mut m = self.0.modified.write().await;
-                    *m = Some(modified);
-                }
+        }
+        Ok(())
+    }
+
+    /// Start the engine with this service's settings, unless it is running.
+    /// A service whose engine settings differ from the running engine's is rejected.
+    async fn start_engine(&self) -> RpcResult<()> {
+        let settings = EngineSettings::new(&self.0.config);
+        // held until the engine is started, so services initialized at once
+        // don't both start it
+        let mut running = ENGINE.lock().await;
+        if engine::is_started().await {
+            let differences = match running.as_ref() {
+                Some(running) => running.differences(&settings),
+                None => Vec::new(),
+            };
+            if !differences.is_empty() {
+                return Err(RpcError::ProviderInit(format!(
+                    "{} can't be changed while the python engine is running",
+                    differences.join(", ")
+                )));
             }
+            return Ok(());
         }
-        // init and start pime
+        if let Some(addr) = &self.0.config.metrics_addr {
+            metrics::serve(addr).await.map_err(|e| {
+                RpcError::ProviderInit(format!("serving metrics on {}: {}", addr, e))
+            })?;
+        }
+        self.init_python()
+            .await
+            .map_err(|e| RpcError::ProviderInit(e.to_string()))?;
+        *running = Some(settings);
+        Ok(())
+    }
+
+    /// start the engine, with settings from the first service
+    async fn init_python(&self) -> Result<(), pime::Error> {
         let config = self.0.config.clone();
+        if let Some(workers) = config.workers.filter(|n| *n > 0) {
+            let python = match &config.venv_path {
+                Some(venv) => format!("{}/bin/python3", venv),
+                None => "python3".to_string(),
+            };
+            let command = worker::WorkerCommand {
+                python,
+                router: ROUTER_SOURCE,
+            };
+            worker::start(command, workers).await;
+            metrics::THREAD_POOL_MAX.set(workers as i64);
+            return Ok(());
+        }
+        // init and start pime
+        let startup_timeout = config
+            .startup_timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(STARTUP_TIMEOUT);
+        // errors before the engine is online are sent back here
+        let (failed_tx, failed_rx) = tokio::sync::oneshot::channel::<pime::Error>();
         let _join = tokio::task::spawn_blocking(move || {
             // omit if auto-prepared
             pyo3::prepare_freethreaded_python();
@@ -112,87 +364,126 @@ impl Service {
                 // all work with the Python object MUST be performed in this thread
                 // after there is no way to reconfigure it
                 let engine = match &config.venv_path {
-                    Some(venv) => pime::PySyncEngine::new_venv(&py, venv).expect("reading venv"),
-                    None => pime::PySyncEngine::new(&py).expect("init engine"),
+                    Some(venv) => pime::PySyncEngine::new_venv(&py, venv)?,
+                    None => pime::PySyncEngine::new(&py)?,
                 };
 
-                // inserts directories into Python's sys.path, starting with the main program dir
-                engine
-                    .add_import_path(py_main.parent().unwrap().to_str().unwrap())
-                    .unwrap();
+                // inserts directories into Python's sys.path
                 for d in config.sys_path.iter() {
-                    engine.add_import_path(d).unwrap();
+                    engine.add_import_path(d)?;
                 }
                 // enables debug mode
                 //engine.enable_debug().unwrap();
+                let max_threads = config.max_threads.unwrap_or(THREAD_POOL_MAX_THREADS);
                 engine.set_thread_pool_size(
                     config.min_threads.unwrap_or(THREAD_POOL_MIN_THREADS) as u32,
-                    config.max_threads.unwrap_or(THREAD_POOL_MAX_THREADS) as u32,
+                    max_threads as u32,
                 )?;
-                let py_main_module = if py_main.is_file() {
-                    py_main.file_stem().unwrap().to_str().unwrap()
-                } else {
-                    py_main.file_name().unwrap().to_str().unwrap()
-                };
-                eprintln!("about to load python main module {}", py_main_module);
-                let module = py.import(py_main_module).unwrap();
-                let broker = module.getattr("main").unwrap();
-                // Perform additional work, e.g. add Rust functions to Python modules
-                // .................................
+                metrics::THREAD_POOL_MAX.set(max_threads as i64);
+                // python_main modules are loaded by the router after the engine starts
+                let module =
+                    PyModule::from_code(py, ROUTER_SOURCE, "router.py", "wasmcloud_router")?;
+                let broker = module.getattr("broker")?;
+                // add Rust functions for python to call back into the lattice
+                host::add_module(py)?;
                 // fire and go
-                engine.launch(&py, broker).unwrap();
+                engine.launch(&py, broker)?;
                 Ok::<(), pime::Error>(())
             });
             if let Err(e) = rc {
                 error!("error starting python: {}", e);
+                let _ = failed_tx.send(e);
             }
         });
-        // wait engine to be started
-        pime::wait_online().await;
+        // wait engine to be started. If the startup thread panics, failed_rx is closed
+        // and only the timeout ends the wait.
+        tokio::select! {
+            _ = pime::wait_online() => Ok(()),
+            Ok(e) = failed_rx => Err(e),
+            _ = tokio::time::sleep(startup_timeout) => Err(pime::Error::new(
+                pime::ErrorKind::PySyncEngineStateError,
+                format!("python engine did not start within {:?}", startup_timeout),
+            )),
+        }
+    }
+
+    /// load (or reuse) this service's python_main module in the engine
+    async fn load_module(&self) -> Result<(), pime::Error> {
+        debug!("Loading python main module {}", &self.0.main_path);
+        let mut params = BTreeMap::new();
+        params.insert("path".to_string(), Value::String(self.0.main_path.clone()));
+        params.insert(
+            "sys_path".to_string(),
+            Value::Seq(
+                self.0
+                    .config
+                    .sys_path
+                    .iter()
+                    .map(|d| Value::String(d.clone()))
+                    .collect(),
+            ),
+        );
+        let mut task = pime::PyTask::new(Value::String(LOAD_COMMAND.to_string()), params);
+        task.mark_exclusive();
+        engine::call(task).await?;
         Ok(())
     }
 
-    /// check last modified date of dispatch module to see if it's changed.
-    /// If so, shut down engine and reload
-    async fn reload_needed(&self) -> bool {
-        // modified date of file at time of last load
-        let previous_modified = self.0.modified.read().await.clone();
-        // modified date of file now
-        let current_modified = {
-            let py_disp = PathBuf::from(&self.0.config.python_dispatch.as_ref().unwrap());
-            if let Ok(md) = std::fs::metadata(&py_disp) {
-                if let Ok(modified) = md.modified() {
-                    Some(modified)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        };
-        match (previous_modified, current_modified) {
-            (None, Some(_)) => true,
-            (Some(l), Some(d)) if d > l => true,
-            _ => false,
-        }
+    /// Invoke python with command and arg, without caller information
+    pub async fn invoke(&self, command: &str, arg: &[u8]) -> Result<Vec<u8>, RpcError> {
+        self.invoke_from(&CallContext::default(), command, arg)
+            .await
     }
 
-    // invoke, checking whether dispatch reload is required
-    pub async fn check_invoke(&self, command: &str, arg: &[u8]) -> Result<Vec<u8>, RpcError> {
-        if self.reload_needed().await {
-            info!("dispatch change detected, reloading...");
-            let _ = self.invoke("reload", &[]).await?;
-        }
-        self.invoke(command, arg).await
+    /// Invoke python with command and arg, on behalf of a caller
+    pub async fn invoke_from(
+        &self,
+        caller: &CallContext,
+        command: &str,
+        arg: &[u8],
+    ) -> Result<Vec<u8>, RpcError> {
+        let start = Instant::now();
+        let result = self.call_python(caller, command, arg).await;
+        let outcome = match &result {
+            Ok(_) => "ok",
+            Err(RpcError::Timeout(_)) => "timeout",
+            Err(RpcError::Ser(_)) | Err(RpcError::Deser(_)) => "invalid",
+            Err(RpcError::Other(e)) if e.starts_with(EXCEPTION_RAISED) => "exception",
+            Err(_) => "error",
+        };
+        metrics::record_call(command, outcome, start.elapsed());
+        result
     }
 
-    /// Invoke python with command and arg
     /// Deserialize param into value for python, serialize result to return to caller
-    pub async fn invoke(&self, command: &str, arg: &[u8]) -> Result<Vec<u8>, RpcError> {
+    async fn call_python(
+        &self,
+        caller: &CallContext,
+        command: &str,
+        arg: &[u8],
+    ) -> Result<Vec<u8>, RpcError> {
+        // wait for a reload in progress to finish
+        let _guard = match &self.0.module {
+            Some(module) => Some(module.enter().await),
+            None => None,
+        };
         let mut params = BTreeMap::new();
-        if !arg.is_empty() {
-            let value: Value =
-                minicbor_ser::from_slice(arg).map_err(|e| RpcError::Ser(e.to_string()))?;
+        caller.add_params(&mut params);
+        params.insert(
+            MAIN_PARAM.to_string(),
+            Value::String(self.0.main_path.clone()),
+        );
+        let value: Option<Value> = if !arg.is_empty() {
+            Some(minicbor_ser::from_slice(arg).map_err(|e| RpcError::Ser(e.to_string()))?)
+        } else {
+            None
+        };
+        if let Some(schema) = &self.0.schema {
+            schema.check_input(command, value.as_ref()).map_err(|e| {
+                RpcError::Deser(format!("invalid parameter for {}: {}", command, e))
+            })?;
+        }
+        if let Some(value) = value {
             debug!("Invoking {}(arg: {:?})", command, &value);
             params.insert("arg".to_string(), value);
         } else {
@@ -205,10 +496,56 @@ impl Service {
         //task.no_wait();
         // If a task performs calculations only, it can be marked as exclusive.
         // Tasks of this type lock Python thread until completed. Use with care!
-        task.mark_exclusive();
-        match pime::call(task).await {
+        let exclusive = self.0.config.is_exclusive(command);
+        let timeout = self.0.config.method_timeout(command);
+        if exclusive {
+            task.mark_exclusive();
+        }
+        if let Some(timeout) = timeout {
+            task.set_timeout(timeout);
+        }
+        let mut result = engine::call(task).await;
+        let stream = match &result {
+            Ok(value) => stream::Stream::from_result(
+                value.as_ref(),
+                self.0.config.stream_chunk_size.unwrap_or(STREAM_CHUNK_SIZE),
+                exclusive,
+                timeout,
+            ),
+            Err(_) => None,
+        };
+        if let Some(stream) = stream {
+            match caller.actor.as_ref() {
+                // send items to the linked actor as they are produced
+                Some(actor_id) if host::is_linked(actor_id).await => {
+                    let mut ack = BTreeMap::new();
+                    ack.insert(Value::String("stream".to_string()), Value::U64(stream.id()));
+                    let method = self
+                        .0
+                        .config
+                        .stream_method
+                        .clone()
+                        .unwrap_or_else(|| STREAM_METHOD.to_string());
+                    let stream = match &self.0.schema {
+                        Some(schema) => stream.with_schema(schema.clone(), command),
+                        None => stream,
+                    };
+                    tokio::spawn(stream.deliver(actor_id.clone(), method));
+                    return minicbor_ser::to_vec(&Value::Map(ack))
+                        .map_err(|e| RpcError::Deser(e.to_string()));
+                }
+                // otherwise return all the items at once
+                _ => result = stream.collect().await.map(Some),
+            }
+        }
+        match result {
             Ok(result) => {
                 debug!("Result: {:?}", &result);
+                if let Some(schema) = &self.0.schema {
+                    schema.check_output(command, result.as_ref()).map_err(|e| {
+                        RpcError::Ser(format!("invalid result from {}: {}", command, e))
+                    })?;
+                }
                 let buf = match result {
                     Some(r) => {
                         minicbor_ser::to_vec(&r).map_err(|e| RpcError::Deser(e.to_string()))?
@@ -218,11 +555,20 @@ impl Service {
                 Ok(buf)
             }
             Err(e) if e.kind == pime::ErrorKind::PyException => {
-                let mut error = format!("Exception raised {}: {}", e.exception.unwrap(), e.message);
+                let exception = e.exception.unwrap();
+                metrics::EXCEPTIONS
+                    .with_label_values(&[metrics::command_label(command), &exception])
+                    .inc();
+                let mut error = format!("{} {}: {}", EXCEPTION_RAISED, exception, e.message);
                 error.push_str(&format!("\n{}\n", e.traceback.unwrap()));
                 error!("{}", &error);
                 Err(RpcError::Other(error))
             }
+            Err(e) if e.kind == pime::ErrorKind::Timeout => {
+                let error = format!("{} timed out: {}", command, e.message);
+                error!("{}", &error);
+                Err(RpcError::Timeout(error))
+            }
             Err(e) => {
                 let error = format!("error: {}", e.message);
                 error!("{}", &error);
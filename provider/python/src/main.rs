@@ -1,15 +1,23 @@
 //! main process for python capability-provider
 //!
 use lazy_static::lazy_static;
-use log::info;
-use pyprov::Service;
+use log::{error, info};
+use pyprov::{CallContext, Service};
 use std::convert::Infallible;
 use tokio::sync::RwLock;
-use wasmbus_rpc::provider::prelude::*;
+use wasmbus_rpc::{
+    async_nats::header::HeaderMap,
+    core::{HealthCheckRequest, HealthCheckResponse},
+    otel::OtelHeaderInjector,
+    provider::prelude::*,
+};
 
-// The Service is a singleton, because of python GIL
+// The python engine is a singleton, because of python GIL.
+// SINGLE_SERVICE is configured by environment variables, and handles actors
+// whose links don't configure their own python_main. It is None if PYTHON_MAIN
+// isn't set, in which case every link must configure its own python_main.
 lazy_static! {
-    static ref SINGLE_SERVICE: RwLock<Service> = RwLock::new(Service::default());
+    static ref SINGLE_SERVICE: RwLock<Option<Service>> = RwLock::new(None);
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -19,17 +27,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     })?;
 
     let runtime = tokio::runtime::Runtime::new().unwrap();
-    runtime.block_on(async move {
-        let mut s = SINGLE_SERVICE.write().await;
-        let service = match Service::try_init(None).await {
-            Ok(service) => service,
-            Err(e) => {
-                eprintln!("ERROR starting service: {}", e.to_string());
-                return;
-            }
-        };
-        *s = service;
-        drop(s);
+    let result = runtime.block_on(async move {
+        if std::env::var_os("PYTHON_MAIN").is_some() {
+            let service = match Service::try_init(None).await {
+                Ok(service) => service,
+                Err(e) => {
+                    eprintln!("ERROR starting service: {}", e.to_string());
+                    // stop the engine if it started before the error
+                    Service::shutdown().await;
+                    return Err(e);
+                }
+            };
+            *SINGLE_SERVICE.write().await = Some(service);
+        } else {
+            info!("PYTHON_MAIN is not set: actors are served only by links with a python_main");
+        }
 
         if let Err(e) = provider_run(
             PythonProvider::default(),
@@ -40,13 +52,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         {
             eprintln!("ERROR provider exited with {}", e.to_string());
         }
+        Ok(())
     });
 
     // in the unlikely case there are any stuck threads,
     // close them so the process has a clean exit
     runtime.shutdown_timeout(core::time::Duration::from_secs(5));
     eprintln!("INFO  python provider exiting");
-    Ok(())
+    // exit with an error status if the service couldn't start
+    result.map_err(|e| e.into())
 }
 
 #[derive(Clone, Default)]
@@ -55,11 +69,48 @@ struct PythonProvider {}
 // use default implementations of provider message handlers
 impl ProviderDispatch for PythonProvider {}
 
-// Because the service is a global singleton, and configured by environment variables,
-// there are no linkdef properties saved per actor link,
-// and we can inherit the default ProviderHandler methods for link handling.
+// Links are saved per actor, so python can be told the caller's link name.
+// Links with config_json or config_b64 values get their own service,
+// so each actor's invocations are routed to its link's python_main module.
 #[async_trait]
 impl ProviderHandler for PythonProvider {
+    async fn put_link(&self, ld: &LinkDefinition) -> RpcResult<bool> {
+        let service =
+            if ld.values.contains_key("config_json") || ld.values.contains_key("config_b64") {
+                match Service::try_init(Some(ld.values.clone())).await {
+                    Ok(service) => Some(service),
+                    Err(e) => {
+                        error!("invalid link for actor {}: {}", &ld.actor_id, e);
+                        return Ok(false);
+                    }
+                }
+            } else {
+                None
+            };
+        pyprov::host::put_link(ld, service).await;
+        Ok(true)
+    }
+
+    async fn delete_link(&self, actor_id: &str) {
+        pyprov::host::delete_link(actor_id).await;
+    }
+
+    /// report the state of the python engine, and whether it can run tasks
+    async fn health_request(&self, _arg: &HealthCheckRequest) -> RpcResult<HealthCheckResponse> {
+        let service = match SINGLE_SERVICE.read().await.clone() {
+            Some(service) => Some(service),
+            None => pyprov::host::any_link_service().await,
+        };
+        match service {
+            Some(service) => Ok(service.health().await),
+            // the engine starts with the first link that has a python_main
+            None => Ok(HealthCheckResponse {
+                healthy: true,
+                message: Some("engine=stopped no python_main is loaded".to_string()),
+            }),
+        }
+    }
+
     async fn shutdown(&self) -> Result<(), Infallible> {
         Service::shutdown().await;
         Ok(())
@@ -68,7 +119,7 @@ impl ProviderHandler for PythonProvider {
 
 #[async_trait]
 impl MessageDispatch for PythonProvider {
-    async fn dispatch(&self, _ctx: &Context, message: Message<'_>) -> Result<Vec<u8>, RpcError> {
+    async fn dispatch(&self, ctx: &Context, message: Message<'_>) -> Result<Vec<u8>, RpcError> {
         use wasmbus_rpc::common::MessageFormat;
 
         let method = message.method;
@@ -87,9 +138,36 @@ impl MessageDispatch for PythonProvider {
             }
         };
 
-        // call into python and return result
-        let service = SINGLE_SERVICE.read().await;
-        let result = service.check_invoke(method, buf).await?;
-        Ok(result)
+        let link = match ctx.actor.as_ref() {
+            Some(actor_id) => pyprov::host::get_link(actor_id).await,
+            None => None,
+        };
+        let caller = CallContext {
+            actor: ctx.actor.clone(),
+            link_name: link.as_ref().map(|link| link.ld.link_name.clone()),
+            // headers of the current span, which continues the actor's trace
+            trace_context: HeaderMap::from(OtelHeaderInjector::default_with_span())
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        String::from_utf8_lossy(name.as_ref()).into_owned(),
+                        value.as_str().to_string(),
+                    )
+                })
+                .collect(),
+        };
+
+        // call into python, with the calling actor's service, and return result
+        let service = match link.and_then(|link| link.service) {
+            Some(service) => service,
+            None => SINGLE_SERVICE.read().await.clone().ok_or_else(|| {
+                RpcError::InvalidParameter(format!(
+                    "no python_main for actor {}: set PYTHON_MAIN, or python_main in the \
+                     actor's link config_json",
+                    ctx.actor.as_deref().unwrap_or_default()
+                ))
+            })?,
+        };
+        service.invoke_from(&caller, method, buf).await
     }
 }
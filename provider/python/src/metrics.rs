@@ -0,0 +1,159 @@
+//! Prometheus metrics for python calls, the engine's task queue, and reloads
+//!
+//! Metrics are served in the prometheus text format on every path of the address in
+//! `PYTHON_METRICS_ADDR` (or the `metrics_addr` setting), e.g., `0.0.0.0:9464`.
+//!
+//! Commands are method names chosen by the calling actors, so only the first
+//! `MAX_COMMAND_LABELS` distinct commands get their own `command` label, and the rest
+//! are counted as `other`.
+
+use lazy_static::lazy_static;
+use log::{debug, error, info};
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge, Encoder, HistogramVec,
+    IntCounterVec, IntGauge, TextEncoder,
+};
+use std::{collections::HashSet, sync::Mutex, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::engine;
+
+/// Maximum number of distinct values of the `command` label
+const MAX_COMMAND_LABELS: usize = 100;
+/// `command` label of commands after the first `MAX_COMMAND_LABELS`
+const OTHER_COMMAND: &str = "other";
+
+lazy_static! {
+    /// commands that have their own label
+    static ref COMMANDS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+    /// python calls by command and outcome (ok, exception, timeout, invalid, error)
+    pub(crate) static ref CALLS: IntCounterVec = register_int_counter_vec!(
+        "python_calls_total",
+        "Number of python calls, by command and outcome",
+        &["command", "outcome"]
+    )
+    .unwrap();
+    /// how long python calls take, by command
+    pub(crate) static ref CALL_SECONDS: HistogramVec = register_histogram_vec!(
+        "python_call_duration_seconds",
+        "Time taken by python calls, by command",
+        &["command"]
+    )
+    .unwrap();
+    /// python exceptions by command and exception type
+    pub(crate) static ref EXCEPTIONS: IntCounterVec = register_int_counter_vec!(
+        "python_exceptions_total",
+        "Number of exceptions raised by python calls, by command and exception",
+        &["command", "exception"]
+    )
+    .unwrap();
+    /// module reloads by result (ok, failed)
+    pub(crate) static ref RELOADS: IntCounterVec = register_int_counter_vec!(
+        "python_reloads_total",
+        "Number of python module reloads, by result",
+        &["result"]
+    )
+    .unwrap();
+    /// tasks sent to the engine that haven't returned, updated when metrics are read
+    static ref TASKS_PENDING: IntGauge = register_int_gauge!(
+        "python_tasks_pending",
+        "Number of python tasks in progress or waiting for a thread"
+    )
+    .unwrap();
+    /// tasks waiting in the engine's queue, updated when metrics are read
+    static ref TASKS_QUEUED: IntGauge = register_int_gauge!(
+        "python_tasks_queued",
+        "Number of python tasks not yet picked up by the engine"
+    )
+    .unwrap();
+    /// configured maximum size of the engine's thread pool
+    pub(crate) static ref THREAD_POOL_MAX: IntGauge = register_int_gauge!(
+        "python_thread_pool_max",
+        "Maximum number of threads in the python engine's thread pool"
+    )
+    .unwrap();
+}
+
+/// Returns the `command` label of a command: the command itself if it already has
+/// a label or there is room for one, otherwise `other`
+pub(crate) fn command_label(command: &str) -> &str {
+    let mut commands = COMMANDS.lock().unwrap();
+    if commands.contains(command) {
+        return command;
+    }
+    if commands.len() < MAX_COMMAND_LABELS {
+        commands.insert(command.to_string());
+        return command;
+    }
+    OTHER_COMMAND
+}
+
+/// record the outcome and duration of a call
+pub(crate) fn record_call(command: &str, outcome: &str, elapsed: Duration) {
+    let command = command_label(command);
+    CALLS.with_label_values(&[command, outcome]).inc();
+    CALL_SECONDS
+        .with_label_values(&[command])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Start serving metrics on an address
+pub(crate) async fn serve(addr: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("serving metrics on {}", addr);
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(async move {
+                        if let Err(e) = respond(stream).await {
+                            debug!("metrics request failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => error!("accepting metrics connection: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// answer any request with the current metrics
+async fn respond(mut stream: TcpStream) -> std::io::Result<()> {
+    // the request doesn't matter, but read it so the client isn't reset
+    let mut request = [0u8; 1024];
+    let _ = stream.read(&mut request).await?;
+
+    TASKS_PENDING.set(engine::pending_tasks().await as i64);
+    TASKS_QUEUED.set(engine::queued_tasks().await as i64);
+    let mut body = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&prometheus::gather(), &mut body) {
+        error!("encoding metrics: {}", e);
+    }
+    let header = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        TextEncoder::new().format_type(),
+        body.len()
+    );
+    stream.write_all(header.as_bytes()).await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn command_labels_are_capped() {
+        for n in 0..MAX_COMMAND_LABELS {
+            let command = format!("m.method{}", n);
+            assert_eq!(command_label(&command), command.as_str());
+        }
+        assert_eq!(command_label("m.another"), OTHER_COMMAND);
+        assert_eq!(command_label("m.method0"), "m.method0");
+    }
+}
@@ -188,6 +188,7 @@ pub enum ErrorKind {
     ExecError,
     InternalError,
     PySyncEngineStateError,
+    Timeout,
 }
 
 impl fmt::Display for ErrorKind {
@@ -202,6 +203,7 @@ impl fmt::Display for ErrorKind {
                 ErrorKind::ExecError => "Task execution error",
                 ErrorKind::InternalError => "Internal error",
                 ErrorKind::PySyncEngineStateError => "Engine state error",
+                ErrorKind::Timeout => "Task timed out",
             }
         )
     }
@@ -307,6 +309,7 @@ pub struct PyTask {
     params: BTreeMap<String, Value>,
     need_result: bool,
     exclusive: bool,
+    timeout: Option<Duration>,
 }
 
 #[allow(dead_code)]
@@ -318,6 +321,7 @@ impl PyTask {
             params,
             need_result: true,
             exclusive: false,
+            timeout: None,
         }
     }
 
@@ -328,6 +332,7 @@ impl PyTask {
             params: BTreeMap::new(),
             need_result: true,
             exclusive: false,
+            timeout: None,
         }
     }
 
@@ -341,6 +346,27 @@ impl PyTask {
         self.exclusive = true;
         self.need_result = true;
     }
+
+    #[must_use]
+    pub fn command(&self) -> &Value {
+        &self.command
+    }
+
+    #[must_use]
+    pub fn params(&self) -> &BTreeMap<String, Value> {
+        &self.params
+    }
+
+    #[must_use]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Limits how long `call` waits for the result. Python can't be interrupted,
+    /// so a task that times out keeps running, but its result is discarded.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
 }
 
 struct DataChannel {
@@ -372,6 +398,8 @@ struct PyTaskResult {
     ready: triggered::Trigger,
     result: Option<Value>,
     error: Option<Error>,
+    /// the caller stopped waiting, so the result is dropped when it arrives
+    abandoned: bool,
 }
 
 impl PyTaskResult {
@@ -442,9 +470,22 @@ macro_rules! log_lost_task {
     };
 }
 
+/// removes the result of a task whose caller has stopped waiting, returning true if it was
+fn discard_abandoned(results: &mut BTreeMap<u64, PyTaskResult>, task_id: u64) -> bool {
+    if results.get(&task_id).is_some_and(|o| o.abandoned) {
+        results.remove(&task_id);
+        debug!("discarding result of timed out task {}", task_id);
+        return true;
+    }
+    false
+}
+
 fn report_error(task_id: u64, error: Error) {
     loop {
         if let Ok(mut v) = PY_RESULTS.try_write() {
+            if discard_abandoned(&mut v, task_id) {
+                break;
+            }
             if let Some(o) = v.get_mut(&task_id) {
                 o.set_error(error);
                 o.ready.trigger();
@@ -478,6 +519,9 @@ fn report_result(
     };
     loop {
         if let Ok(mut v) = PY_RESULTS.try_write() {
+            if discard_abandoned(&mut v, task_id) {
+                break;
+            }
             if let Some(o) = v.get_mut(&task_id) {
                 o.set_result(data);
                 if let Some(e) = error {
@@ -722,6 +766,7 @@ pub async fn call(task: PyTask) -> Result<Option<Value>, Error> {
             .await?;
         return Ok(None);
     }
+    let timeout = task.timeout;
     let (trigger, listener) = triggered::trigger();
     let task_id = loop {
         let cid = TASK_COUNTER.lock().await.get();
@@ -731,6 +776,7 @@ pub async fn call(task: PyTask) -> Result<Option<Value>, Error> {
                 result: None,
                 error: None,
                 ready: trigger,
+                abandoned: false,
             });
             break cid;
         }
@@ -743,7 +789,26 @@ pub async fn call(task: PyTask) -> Result<Option<Value>, Error> {
         .await
         .send((task_id, Some(task)))
         .await?;
-    listener.await;
+    match timeout {
+        Some(timeout) => {
+            if tokio::time::timeout(timeout, listener).await.is_err() {
+                let mut results = PY_RESULTS.write().await;
+                match results.get_mut(&task_id) {
+                    // the result arrived just as the timeout elapsed
+                    Some(o) if o.ready.is_triggered() => {}
+                    Some(o) => {
+                        o.abandoned = true;
+                        return Err(Error::new(
+                            ErrorKind::Timeout,
+                            format!("no result after {:?}", timeout),
+                        ));
+                    }
+                    None => {}
+                }
+            }
+        }
+        None => listener.await,
+    }
     PY_RESULTS.write().await.remove(&task_id).map_or_else(
         || {
             Err(Error::new(
@@ -765,11 +830,23 @@ pub async fn stop() -> Result<(), Error> {
     Ok(())
 }
 
-/*
+#[must_use]
 pub fn get_engine_state() -> u8 {
     ENGINE_STATE.load(Ordering::SeqCst)
 }
- */
+
+/// Returns the number of tasks waiting for a result, including tasks that timed out
+/// but are still running
+pub async fn pending_tasks() -> usize {
+    PY_RESULTS.read().await.len()
+}
+
+/// Returns the number of tasks sent to the engine that it hasn't picked up yet
+pub async fn queued_tasks() -> usize {
+    let dc = DC.read().await;
+    let tx = dc.tx.lock().await;
+    DATACHANNEL_DEFAULT_BUFFER.saturating_sub(tx.capacity())
+}
 
 #[must_use]
 pub fn is_engine_started() -> bool {
@@ -0,0 +1,185 @@
+# Broker for the python provider's engine.
+# Routes each task to the main function of the python_main module it was sent for,
+# so links with different python_main modules can share one interpreter.
+import asyncio
+import collections.abc
+import importlib
+import itertools
+import os
+import sys
+import threading
+
+# main function of each loaded python_main module, by path
+_mains = {}
+
+# generators and iterators returned by main functions, by stream id
+_streams = {}
+_stream_ids = itertools.count(1)
+
+
+# import the python_main module (a package folder or a .py file) at path
+def load(path, sys_path):
+    for d in reversed(sys_path):
+        if d not in sys.path:
+            sys.path.insert(0, d)
+    parent, name = os.path.split(path)
+    if os.path.isfile(path):
+        name = os.path.splitext(name)[0]
+    if parent not in sys.path:
+        sys.path.insert(0, parent)
+    module = importlib.import_module(name)
+    origin = module.__file__
+    if os.path.isdir(path):
+        origin = os.path.dirname(origin)
+    if os.path.realpath(origin) != os.path.realpath(path):
+        raise ImportError(
+            "python_main '{}' conflicts with module '{}' already loaded from '{}'".format(
+                path, name, origin
+            )
+        )
+    _mains[path] = module.main
+    return True
+
+
+# names of loaded modules whose source is path or one of files,
+# or is in the path or sys_path folders
+def _module_names(path, sys_path, files=()):
+    roots = [os.path.realpath(d) for d in [path] + list(sys_path) + list(files)]
+    names = []
+    for name, module in list(sys.modules.items()):
+        origin = getattr(module, "__file__", None)
+        if not origin:
+            continue
+        origin = os.path.realpath(origin)
+        if any(origin == root or origin.startswith(root + os.sep) for root in roots):
+            names.append(name)
+    return names
+
+
+# re-import the python_main module at path, and the modules it uses from its package,
+# sys_path folders, and other files (such as python_dispatch). If the new code fails
+# to import, the previous modules are kept.
+def reload(path, sys_path, files=()):
+    previous = {
+        name: sys.modules.pop(name) for name in _module_names(path, sys_path, files)
+    }
+    previous_main = _mains.get(path)
+    try:
+        importlib.invalidate_caches()
+        return load(path, sys_path)
+    except BaseException:
+        for name in _module_names(path, sys_path, files):
+            sys.modules.pop(name, None)
+        sys.modules.update(previous)
+        if previous_main is not None:
+            _mains[path] = previous_main
+        raise
+
+
+# items of a generator or async iterator returned by a main function
+class Stream:
+    def __init__(self, source):
+        self.source = source
+        # async iterators run in their own event loop, which lasts as long as the stream
+        self.loop = asyncio.new_event_loop() if hasattr(source, "__anext__") else None
+        # a stream closed while a thread is pulling items (e.g., after a timeout)
+        # is closed by that thread when it's done
+        self.lock = threading.Lock()
+        self.running = False
+        self.closed = False
+
+    # return up to count items, and whether the stream is done
+    def next(self, count):
+        with self.lock:
+            if self.closed:
+                return [], True
+            self.running = True
+        try:
+            return self._next(count)
+        finally:
+            with self.lock:
+                self.running = False
+                closed = self.closed
+            if closed:
+                self._close()
+
+    def _next(self, count):
+        items = []
+        try:
+            while len(items) < count:
+                if self.loop:
+                    items.append(self.loop.run_until_complete(self.source.__anext__()))
+                else:
+                    items.append(next(self.source))
+        except (StopIteration, StopAsyncIteration):
+            return items, True
+        return items, False
+
+    def close(self):
+        with self.lock:
+            self.closed = True
+            if self.running:
+                return
+        self._close()
+
+    def _close(self):
+        if self.loop:
+            if hasattr(self.source, "aclose"):
+                self.loop.run_until_complete(self.source.aclose())
+            self.loop.close()
+        elif hasattr(self.source, "close"):
+            self.source.close()
+
+
+def _is_stream(result):
+    return isinstance(result, (collections.abc.Iterator, collections.abc.AsyncIterator))
+
+
+# return the next items of a stream, closing it when it's done
+def next_items(stream_id, count):
+    stream = _streams[stream_id]
+    try:
+        items, done = stream.next(count)
+    except BaseException:
+        close(stream_id)
+        raise
+    if done:
+        close(stream_id)
+    return {"items": items, "done": done}
+
+
+def close(stream_id):
+    stream = _streams.pop(stream_id, None)
+    if stream:
+        stream.close()
+    return True
+
+
+# report that the engine can run tasks, with a summary of its state
+def health():
+    return {
+        "modules": len(_mains),
+        "streams": len(_streams),
+        "threads": threading.active_count(),
+    }
+
+
+# pass command to the main function of the module named in params
+def broker(command, params):
+    if command == "__health__":
+        return health()
+    if command == "__load__":
+        return load(params["path"], params["sys_path"])
+    if command == "__reload__":
+        return reload(params["path"], params["sys_path"], params.get("files", []))
+    if command == "__next__":
+        return next_items(params["stream"], params["count"])
+    if command == "__close__":
+        return close(params["stream"])
+    main = params.pop("__main__")
+    result = _mains[main](command, params)
+    if _is_stream(result):
+        stream_id = next(_stream_ids)
+        _streams[stream_id] = Stream(result)
+        return {"__stream__": stream_id}
+    return result
@@ -0,0 +1,436 @@
+//! Check python parameters and results against a smithy model
+//!
+//! The model is the JSON AST form of a smithy model (`{"smithy": "1.0", "shapes": {..}}`).
+//! Each operation of a service in the model is invoked with the method name
+//! `Service.Operation`. Methods that aren't in the model aren't checked.
+//! Items of a streamed result are checked against the member of the output list.
+
+use serde::Deserialize;
+use serde_value::Value;
+use std::collections::{BTreeMap, HashMap};
+
+const REQUIRED_TRAIT: &str = "smithy.api#required";
+
+/// wasmcloud model shapes of unsigned integers
+const UNSIGNED_SHAPES: &[&str] = &[
+    "org.wasmcloud.model#U8",
+    "org.wasmcloud.model#U16",
+    "org.wasmcloud.model#U32",
+    "org.wasmcloud.model#U64",
+];
+
+/// A smithy model in JSON AST form
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct Schema {
+    #[serde(default)]
+    shapes: HashMap<String, Shape>,
+    /// input and output shape of each method
+    #[serde(skip)]
+    methods: HashMap<String, Operation>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Shape {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    operations: Vec<Target>,
+    input: Option<Target>,
+    output: Option<Target>,
+    #[serde(default)]
+    members: BTreeMap<String, Member>,
+    member: Option<Target>,
+    key: Option<Target>,
+    value: Option<Target>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Target {
+    target: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Member {
+    target: String,
+    #[serde(default)]
+    traits: HashMap<String, serde_json::Value>,
+}
+
+/// input and output shape ids of an operation
+#[derive(Debug, Default)]
+struct Operation {
+    input: Option<String>,
+    output: Option<String>,
+}
+
+impl Schema {
+    /// load a model from a JSON AST file
+    pub(crate) fn load(path: &str) -> Result<Schema, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("reading schema '{}': {}", path, e))?;
+        Schema::from_slice(&bytes).map_err(|e| format!("invalid schema '{}': {}", path, e))
+    }
+
+    fn from_slice(bytes: &[u8]) -> Result<Schema, serde_json::Error> {
+        let mut schema: Schema = serde_json::from_slice(bytes)?;
+        let mut methods = HashMap::new();
+        for (service_id, service) in schema.shapes.iter() {
+            if service.kind != "service" {
+                continue;
+            }
+            for op in service.operations.iter() {
+                if let Some(shape) = schema.shapes.get(&op.target) {
+                    let method = format!("{}.{}", shape_name(service_id), shape_name(&op.target));
+                    methods.insert(
+                        method,
+                        Operation {
+                            input: shape.input.as_ref().map(|t| t.target.clone()),
+                            output: shape.output.as_ref().map(|t| t.target.clone()),
+                        },
+                    );
+                }
+            }
+        }
+        schema.methods = methods;
+        Ok(schema)
+    }
+
+    /// check the parameter of a method. Returns the path and reason of the first mismatch.
+    pub(crate) fn check_input(&self, method: &str, arg: Option<&Value>) -> Result<(), String> {
+        match self.methods.get(method) {
+            Some(op) => self.check_operand(op.input.as_deref(), arg),
+            None => Ok(()),
+        }
+    }
+
+    /// check the result of a method. Returns the path and reason of the first mismatch.
+    pub(crate) fn check_output(&self, method: &str, result: Option<&Value>) -> Result<(), String> {
+        match self.methods.get(method) {
+            Some(op) => self.check_operand(op.output.as_deref(), result),
+            None => Ok(()),
+        }
+    }
+
+    /// check an item of a method's streamed result, which is the item at `index` of
+    /// the output list
+    pub(crate) fn check_stream_item(
+        &self,
+        method: &str,
+        index: usize,
+        item: &Value,
+    ) -> Result<(), String> {
+        let op = match self.methods.get(method) {
+            Some(op) => op,
+            None => return Ok(()),
+        };
+        let output = match op.output.as_deref() {
+            Some(output) => output,
+            None => return Err("$: expected no value, found stream".to_string()),
+        };
+        match self.shapes.get(output) {
+            Some(shape) if shape.kind == "list" || shape.kind == "set" => match &shape.member {
+                Some(member) => self.check(&format!("$[{}]", index), &member.target, item),
+                None => Ok(()),
+            },
+            _ => Err(format!("$: expected {}, found stream", shape_name(output))),
+        }
+    }
+
+    fn check_operand(&self, shape_id: Option<&str>, value: Option<&Value>) -> Result<(), String> {
+        match (shape_id, value.filter(|v| !is_null(v))) {
+            (Some(shape_id), Some(value)) => self.check("$", shape_id, value),
+            (Some(shape_id), None) => Err(format!("$: missing {}", shape_name(shape_id))),
+            (None, Some(_)) => Err("$: expected no value".to_string()),
+            (None, None) => Ok(()),
+        }
+    }
+
+    fn check(&self, path: &str, shape_id: &str, value: &Value) -> Result<(), String> {
+        let value = unwrap_option(value);
+        let kind = match self.shapes.get(shape_id) {
+            Some(shape) => shape.kind.as_str(),
+            // prelude and wasmcloud model shapes aren't in the model file
+            None => simple_type(shape_name(shape_id)),
+        };
+        let mismatch = || {
+            Err(format!(
+                "{}: expected {}, found {}",
+                path,
+                kind,
+                type_of(value)
+            ))
+        };
+        match kind {
+            "structure" | "union" => {
+                let shape = &self.shapes[shape_id];
+                let fields = match value {
+                    Value::Map(fields) => fields,
+                    _ => return mismatch(),
+                };
+                for name in fields.keys() {
+                    let name = match field_name(name) {
+                        Some(name) => name,
+                        None => return Err(format!("{}: field names must be strings", path)),
+                    };
+                    if !shape.members.contains_key(name) {
+                        return Err(format!("{}.{}: unknown field", path, name));
+                    }
+                }
+                let mut present = 0;
+                for (name, member) in shape.members.iter() {
+                    let field_path = format!("{}.{}", path, name);
+                    match fields
+                        .get(&Value::String(name.clone()))
+                        .filter(|v| !is_null(v))
+                    {
+                        Some(field) => {
+                            present += 1;
+                            self.check(&field_path, &member.target, field)?;
+                        }
+                        None if kind == "structure"
+                            && member.traits.contains_key(REQUIRED_TRAIT) =>
+                        {
+                            return Err(format!("{}: missing required field", field_path));
+                        }
+                        None => {}
+                    }
+                }
+                if kind == "union" && present != 1 {
+                    return Err(format!("{}: union must have exactly one field", path));
+                }
+                Ok(())
+            }
+            "list" | "set" => {
+                let member = &self.shapes[shape_id].member;
+                match value {
+                    Value::Seq(items) => {
+                        if let Some(member) = member {
+                            for (i, item) in items.iter().enumerate() {
+                                self.check(&format!("{}[{}]", path, i), &member.target, item)?;
+                            }
+                        }
+                        Ok(())
+                    }
+                    _ => mismatch(),
+                }
+            }
+            "map" => {
+                let shape = &self.shapes[shape_id];
+                match value {
+                    Value::Map(entries) => {
+                        for (key, item) in entries.iter() {
+                            let key_path = match field_name(key) {
+                                Some(name) => format!("{}[{:?}]", path, name),
+                                None => format!("{}[{:?}]", path, key),
+                            };
+                            if let Some(k) = &shape.key {
+                                self.check(&key_path, &k.target, key)?;
+                            }
+                            if let Some(v) = &shape.value {
+                                self.check(&key_path, &v.target, item)?;
+                            }
+                        }
+                        Ok(())
+                    }
+                    _ => mismatch(),
+                }
+            }
+            "string" | "enum" => match value {
+                Value::String(_) => Ok(()),
+                _ => mismatch(),
+            },
+            "boolean" => match value {
+                Value::Bool(_) => Ok(()),
+                _ => mismatch(),
+            },
+            "blob" => match value {
+                Value::Bytes(_) | Value::Seq(_) => Ok(()),
+                _ => mismatch(),
+            },
+            "float" | "double" | "bigDecimal" => match value {
+                Value::F32(_) | Value::F64(_) => Ok(()),
+                v if integer(v).is_some() => Ok(()),
+                _ => mismatch(),
+            },
+            "bigInteger" => match integer(value) {
+                Some(_) => Ok(()),
+                None => mismatch(),
+            },
+            "byte" | "short" | "integer" | "long" => {
+                let bits = match kind {
+                    "byte" => 8,
+                    "short" => 16,
+                    "integer" => 32,
+                    _ => 64,
+                };
+                let unsigned = UNSIGNED_SHAPES.contains(&shape_id);
+                let (min, max) = if unsigned {
+                    (0i128, (1i128 << bits) - 1)
+                } else {
+                    (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+                };
+                match integer(value) {
+                    Some(n) if n >= min && n <= max => Ok(()),
+                    Some(n) => Err(format!("{}: {} is out of range for {}", path, n, kind)),
+                    None => mismatch(),
+                }
+            }
+            // timestamp, document, and anything else
+            _ => Ok(()),
+        }
+    }
+}
+
+/// the name of a shape, without its namespace
+fn shape_name(shape_id: &str) -> &str {
+    shape_id.rsplit('#').next().unwrap_or(shape_id)
+}
+
+/// the type of prelude shapes and wasmcloud model numeric shapes
+fn simple_type(name: &str) -> &'static str {
+    match name {
+        "String" => "string",
+        "Boolean" | "PrimitiveBoolean" => "boolean",
+        "Byte" | "PrimitiveByte" | "I8" | "U8" => "byte",
+        "Short" | "PrimitiveShort" | "I16" | "U16" => "short",
+        "Integer" | "PrimitiveInteger" | "I32" | "U32" => "integer",
+        "Long" | "PrimitiveLong" | "I64" | "U64" => "long",
+        "Float" | "PrimitiveFloat" | "F32" => "float",
+        "Double" | "PrimitiveDouble" | "F64" => "double",
+        "BigInteger" => "bigInteger",
+        "BigDecimal" => "bigDecimal",
+        "Blob" => "blob",
+        _ => "document",
+    }
+}
+
+fn unwrap_option(value: &Value) -> &Value {
+    match value {
+        Value::Option(Some(v)) | Value::Newtype(v) => unwrap_option(v),
+        v => v,
+    }
+}
+
+fn is_null(value: &Value) -> bool {
+    matches!(unwrap_option(value), Value::Unit | Value::Option(None))
+}
+
+fn field_name(value: &Value) -> Option<&str> {
+    match unwrap_option(value) {
+        Value::String(s) => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+fn integer(value: &Value) -> Option<i128> {
+    match value {
+        Value::U8(n) => Some(*n as i128),
+        Value::U16(n) => Some(*n as i128),
+        Value::U32(n) => Some(*n as i128),
+        Value::U64(n) => Some(*n as i128),
+        Value::I8(n) => Some(*n as i128),
+        Value::I16(n) => Some(*n as i128),
+        Value::I32(n) => Some(*n as i128),
+        Value::I64(n) => Some(*n as i128),
+        _ => None,
+    }
+}
+
+/// describe a value's type for error messages
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "boolean",
+        Value::String(_) | Value::Char(_) => "string",
+        Value::F32(_) | Value::F64(_) => "float",
+        Value::Bytes(_) => "bytes",
+        Value::Seq(_) => "list",
+        Value::Map(_) => "map",
+        Value::Unit | Value::Option(None) => "null",
+        v if integer(v).is_some() => "integer",
+        _ => "value",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MODEL: &str = r#"{
+        "smithy": "1.0",
+        "shapes": {
+            "com.example#Counter": {
+                "type": "service",
+                "operations": [
+                    { "target": "com.example#Add" },
+                    { "target": "com.example#Rows" }
+                ]
+            },
+            "com.example#Add": {
+                "type": "operation",
+                "input": { "target": "com.example#UserCount" },
+                "output": { "target": "org.wasmcloud.model#U32" }
+            },
+            "com.example#Rows": {
+                "type": "operation",
+                "output": { "target": "com.example#RowList" }
+            },
+            "com.example#UserCount": { "type": "integer" },
+            "com.example#RowList": {
+                "type": "list",
+                "member": { "target": "com.example#Row" }
+            },
+            "com.example#Row": {
+                "type": "structure",
+                "members": {
+                    "name": {
+                        "target": "smithy.api#String",
+                        "traits": { "smithy.api#required": {} }
+                    }
+                }
+            }
+        }
+    }"#;
+
+    fn row(name: &str) -> Value {
+        let mut fields = BTreeMap::new();
+        fields.insert(
+            Value::String("name".to_string()),
+            Value::String(name.to_string()),
+        );
+        Value::Map(fields)
+    }
+
+    #[test]
+    fn unsigned_shapes() {
+        let schema = Schema::from_slice(MODEL.as_bytes()).unwrap();
+        // a model shape whose name starts with U is signed
+        assert!(schema
+            .check_input("Counter.Add", Some(&Value::I32(-5)))
+            .is_ok());
+        assert!(schema
+            .check_output("Counter.Add", Some(&Value::U32(5)))
+            .is_ok());
+        let err = schema
+            .check_output("Counter.Add", Some(&Value::I32(-5)))
+            .unwrap_err();
+        assert!(err.contains("out of range"), "{}", err);
+    }
+
+    #[test]
+    fn stream_items() {
+        let schema = Schema::from_slice(MODEL.as_bytes()).unwrap();
+        assert!(schema
+            .check_stream_item("Counter.Rows", 0, &row("a"))
+            .is_ok());
+        let err = schema
+            .check_stream_item("Counter.Rows", 3, &Value::Map(BTreeMap::new()))
+            .unwrap_err();
+        assert_eq!(err, "$[3].name: missing required field");
+        let err = schema
+            .check_stream_item("Counter.Add", 0, &Value::U32(1))
+            .unwrap_err();
+        assert!(err.contains("found stream"), "{}", err);
+        assert!(schema
+            .check_stream_item("Other.Method", 0, &Value::Unit)
+            .is_ok());
+    }
+}
@@ -0,0 +1,201 @@
+//! Results of python generators and async iterators
+//!
+//! When a python function returns a generator or iterator, the router keeps it and
+//! returns a stream id instead. Items are pulled from the stream in chunks, and either
+//! sent to the calling actor as they are produced, or collected into a list.
+//!
+//! Chunks sent to an actor are messages with the stream id, a sequence number starting
+//! at 1, the chunk's items, and whether it is the last chunk. The next chunk isn't
+//! pulled from python until the actor has returned, so a slow actor slows the stream
+//! down instead of letting chunks pile up. If the actor returns `false`, the stream is
+//! closed without sending the rest. If the service has a schema, the items sent to an
+//! actor are checked against the method's output list, and the stream ends with an error
+//! at the first item that doesn't match.
+
+use log::{debug, error};
+use serde_value::Value;
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use crate::{engine, host, pime, schema::Schema};
+
+/// key of the result that the router returns in place of a generator
+const STREAM_KEY: &str = "__stream__";
+/// broker command that returns the next items of a stream
+const NEXT_COMMAND: &str = "__next__";
+/// broker command that closes a stream
+const CLOSE_COMMAND: &str = "__close__";
+
+/// A generator or iterator in the engine
+pub(crate) struct Stream {
+    id: u64,
+    /// maximum number of items in a chunk
+    chunk_size: u32,
+    /// whether to pull items in the engine's main thread
+    exclusive: bool,
+    /// how long to wait for each chunk
+    timeout: Option<Duration>,
+    /// model to check items against, and the method that returned the stream
+    schema: Option<(Arc<Schema>, String)>,
+}
+
+impl Stream {
+    /// Returns the stream for a result, if the python function returned a generator
+    pub(crate) fn from_result(
+        result: Option<&Value>,
+        chunk_size: u32,
+        exclusive: bool,
+        timeout: Option<Duration>,
+    ) -> Option<Stream> {
+        let fields = match result {
+            Some(Value::Map(fields)) if fields.len() == 1 => fields,
+            _ => return None,
+        };
+        let id = match fields.get(&Value::String(STREAM_KEY.to_string()))? {
+            Value::U64(id) => *id,
+            Value::I64(id) => *id as u64,
+            _ => return None,
+        };
+        Some(Stream {
+            id,
+            chunk_size,
+            exclusive,
+            timeout,
+            schema: None,
+        })
+    }
+
+    /// Checks the items sent to an actor against the method's output shape
+    pub(crate) fn with_schema(mut self, schema: Arc<Schema>, method: &str) -> Stream {
+        self.schema = Some((schema, method.to_string()));
+        self
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the next chunk of items, and whether the stream is done
+    async fn next(&self) -> Result<(Vec<Value>, bool), pime::Error> {
+        let mut params = BTreeMap::new();
+        params.insert("stream".to_string(), Value::U64(self.id));
+        params.insert("count".to_string(), Value::U32(self.chunk_size));
+        let mut task = pime::PyTask::new(Value::String(NEXT_COMMAND.to_string()), params);
+        if self.exclusive {
+            task.mark_exclusive();
+        }
+        if let Some(timeout) = self.timeout {
+            task.set_timeout(timeout);
+        }
+        let mut chunk = match engine::call(task).await? {
+            Some(Value::Map(chunk)) => chunk,
+            other => {
+                return Err(pime::Error::new(
+                    pime::ErrorKind::UnpackError,
+                    format!("invalid stream chunk: {:?}", other),
+                ))
+            }
+        };
+        let items = match chunk.remove(&Value::String("items".to_string())) {
+            Some(Value::Seq(items)) => items,
+            _ => Vec::new(),
+        };
+        let done = !matches!(
+            chunk.remove(&Value::String("done".to_string())),
+            Some(Value::Bool(false))
+        );
+        Ok((items, done))
+    }
+
+    /// Checks a chunk of items, the first of which is item number `first` of the stream
+    fn check_items(&self, first: usize, items: &[Value]) -> Result<(), String> {
+        if let Some((schema, method)) = &self.schema {
+            for (i, item) in items.iter().enumerate() {
+                schema
+                    .check_stream_item(method, first + i, item)
+                    .map_err(|e| format!("invalid result from {}: {}", method, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops the python generator before it is done
+    async fn close(&self) {
+        let mut params = BTreeMap::new();
+        params.insert("stream".to_string(), Value::U64(self.id));
+        let task = pime::PyTask::new(Value::String(CLOSE_COMMAND.to_string()), params);
+        if let Err(e) = engine::call(task).await {
+            error!("closing stream {}: {}", self.id, e);
+        }
+    }
+
+    /// Returns all the items of the stream as a list. The stream is closed if a chunk
+    /// fails or times out, like in [Stream::deliver].
+    pub(crate) async fn collect(self) -> Result<Value, pime::Error> {
+        let mut all = Vec::new();
+        loop {
+            match self.next().await {
+                Ok((items, done)) => {
+                    all.extend(items);
+                    if done {
+                        return Ok(Value::Seq(all));
+                    }
+                }
+                Err(e) => {
+                    self.close().await;
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Sends chunks of the stream to an actor, until the stream is done, fails,
+    /// or the actor returns false
+    pub(crate) async fn deliver(self, actor_id: String, method: String) {
+        let mut sequence = 0u64;
+        let mut sent = 0usize;
+        loop {
+            sequence += 1;
+            let mut message = BTreeMap::new();
+            message.insert(Value::String("stream".to_string()), Value::U64(self.id));
+            message.insert(Value::String("sequence".to_string()), Value::U64(sequence));
+            let chunk = match self.next().await {
+                Ok((items, done)) => self.check_items(sent, &items).map(|_| (items, done)),
+                Err(e) => Err(e.to_string()),
+            };
+            let done = match chunk {
+                Ok((items, done)) => {
+                    sent += items.len();
+                    message.insert(Value::String("items".to_string()), Value::Seq(items));
+                    done
+                }
+                Err(e) => {
+                    // the router closes a stream whose generator raised, but a stream
+                    // whose chunk timed out or failed the schema check is still open
+                    self.close().await;
+                    message.insert(Value::String("items".to_string()), Value::Seq(Vec::new()));
+                    message.insert(Value::String("error".to_string()), Value::String(e));
+                    true
+                }
+            };
+            message.insert(Value::String("done".to_string()), Value::Bool(done));
+            match host::send(&actor_id, &method, Some(Value::Map(message))).await {
+                Ok(Value::Bool(false)) if !done => {
+                    debug!("actor {} stopped stream {}", &actor_id, self.id);
+                    self.close().await;
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    error!("sending stream {} to actor {}: {}", self.id, &actor_id, e);
+                    if !done {
+                        self.close().await;
+                    }
+                    return;
+                }
+            }
+            if done {
+                return;
+            }
+        }
+    }
+}
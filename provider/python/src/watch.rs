@@ -0,0 +1,211 @@
+//! Reload python_main modules when their files change
+//!
+//! The python_main package (or file), the sys_path folders, and the python_dispatch
+//! file of each module are watched for changes. Once changes stop for a moment, the
+//! module is reloaded in the background, after invocations in progress for the module
+//! have finished.
+
+use lazy_static::lazy_static;
+use log::{debug, error, info};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde_value::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+use tokio::sync::{mpsc, RwLock};
+
+use crate::{engine, metrics, pime, worker};
+
+/// how long files must be unchanged before a module is reloaded
+const DEBOUNCE: Duration = Duration::from_millis(500);
+/// broker command that reloads a python_main module
+const RELOAD_COMMAND: &str = "__reload__";
+
+lazy_static! {
+    /// module of each python_main path, shared by all services using it
+    static ref MODULES: Mutex<HashMap<String, Weak<Module>>> = Mutex::new(HashMap::new());
+}
+
+/// A python_main module loaded into the engine
+pub(crate) struct Module {
+    main_path: String,
+    /// held for reading by invocations, and for writing while reloading
+    gate: Arc<RwLock<()>>,
+    /// dropping the watcher stops the reload task
+    _watcher: Option<RecommendedWatcher>,
+}
+
+impl std::fmt::Debug for Module {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Module").finish()
+    }
+}
+
+/// When the last service using the module is dropped (e.g., its link is deleted),
+/// restarted python workers no longer load it
+impl Drop for Module {
+    fn drop(&mut self) {
+        // unless a new module for the path has replaced this one
+        let modules = MODULES.lock().unwrap();
+        if !modules
+            .get(&self.main_path)
+            .is_some_and(|m| m.strong_count() > 0)
+        {
+            worker::forget(&self.main_path);
+        }
+    }
+}
+
+impl Module {
+    /// Returns the module for a python_main path, and starts watching its files
+    /// if it isn't already in use. The files stop being watched when the last
+    /// service using the module is dropped.
+    pub(crate) fn get(
+        main_path: &str,
+        sys_path: &[String],
+        python_dispatch: Option<&str>,
+    ) -> Arc<Module> {
+        let mut modules = MODULES.lock().unwrap();
+        if let Some(module) = modules.get(main_path).and_then(Weak::upgrade) {
+            return module;
+        }
+        let gate = Arc::new(RwLock::new(()));
+        let files = python_dispatch
+            .map(|path| canonical(path).to_string_lossy().to_string())
+            .into_iter()
+            .collect::<Vec<_>>();
+        let watcher = match watch(main_path, sys_path, files, gate.clone()) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                error!("not watching {} for changes: {}", main_path, e);
+                None
+            }
+        };
+        let module = Arc::new(Module {
+            main_path: main_path.to_string(),
+            gate,
+            _watcher: watcher,
+        });
+        modules.retain(|_, m| m.strong_count() > 0);
+        modules.insert(main_path.to_string(), Arc::downgrade(&module));
+        module
+    }
+
+    /// Wait for any reload in progress, and prevent reloads until the guard is dropped
+    pub(crate) async fn enter(&self) -> tokio::sync::RwLockReadGuard<'_, ()> {
+        self.gate.read().await
+    }
+}
+
+/// watch the module's files, and start the task that reloads it.
+/// The folder of each of `files` is watched, for editors that save by replacing the file,
+/// but only changes to the file itself reload the module.
+fn watch(
+    main_path: &str,
+    sys_path: &[String],
+    files: Vec<String>,
+    gate: Arc<RwLock<()>>,
+) -> Result<RecommendedWatcher, notify::Error> {
+    let roots: Vec<PathBuf> = std::iter::once(main_path)
+        .chain(sys_path.iter().map(String::as_str))
+        .map(canonical)
+        .collect();
+    let watched: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+        Ok(event) if is_source_change(&event, &roots, &watched) => {
+            let _ = tx.send(());
+        }
+        Ok(_) => {}
+        Err(e) => error!("file watch error: {}", e),
+    })?;
+    watcher.watch(Path::new(main_path), RecursiveMode::Recursive)?;
+    for dir in sys_path.iter() {
+        watcher.watch(Path::new(dir), RecursiveMode::Recursive)?;
+    }
+    for file in files.iter() {
+        if let Some(dir) = Path::new(file).parent() {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+    }
+    tokio::spawn(reload_on_change(
+        main_path.to_string(),
+        sys_path.to_vec(),
+        files,
+        gate,
+        rx,
+    ));
+    Ok(watcher)
+}
+
+/// absolute path with symlinks resolved, or the path as given if it doesn't exist
+fn canonical(path: &str) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+/// whether an event changed python source in one of the roots, or one of the files,
+/// ignoring compiled files
+fn is_source_change(event: &Event, roots: &[PathBuf], files: &[PathBuf]) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event.paths.iter().any(|p| {
+            p.extension() == Some(OsStr::new("py"))
+                && (files.contains(p) || roots.iter().any(|root| p.starts_with(root)))
+        })
+}
+
+/// reload the module after changes, until the watcher is dropped
+async fn reload_on_change(
+    main_path: String,
+    sys_path: Vec<String>,
+    files: Vec<String>,
+    gate: Arc<RwLock<()>>,
+    mut rx: mpsc::UnboundedReceiver<()>,
+) {
+    while rx.recv().await.is_some() {
+        // wait for changes to stop
+        loop {
+            match tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                Ok(Some(())) => continue,
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+        // wait for invocations to finish, and hold new ones until reloaded
+        let _reloading = gate.write().await;
+        info!("change detected, reloading {}", &main_path);
+        match reload(&main_path, &sys_path, &files).await {
+            Ok(()) => {
+                metrics::RELOADS.with_label_values(&["ok"]).inc();
+                debug!("reloaded {}", &main_path);
+            }
+            Err(e) => {
+                metrics::RELOADS.with_label_values(&["failed"]).inc();
+                error!(
+                    "reloading {} failed, keeping previous version: {}",
+                    &main_path, e
+                );
+            }
+        }
+    }
+}
+
+async fn reload(main_path: &str, sys_path: &[String], files: &[String]) -> Result<(), pime::Error> {
+    let mut params = BTreeMap::new();
+    params.insert("path".to_string(), Value::String(main_path.to_string()));
+    params.insert(
+        "sys_path".to_string(),
+        Value::Seq(sys_path.iter().map(|d| Value::String(d.clone())).collect()),
+    );
+    params.insert(
+        "files".to_string(),
+        Value::Seq(files.iter().map(|f| Value::String(f.clone())).collect()),
+    );
+    let mut task = pime::PyTask::new(Value::String(RELOAD_COMMAND.to_string()), params);
+    task.mark_exclusive();
+    engine::call(task).await?;
+    Ok(())
+}
@@ -0,0 +1,81 @@
+# Worker process for the python provider's subprocess mode, run after router.py.
+# Reads requests from stdin and writes responses to stdout, one at a time. Each message
+# is a CBOR map, preceded by its length as a 4-byte big-endian integer.
+#   request: {"command": str, "params": map}
+#   response: {"result": value} or {"error": [exception, message, traceback]}
+import struct
+import sys
+import traceback
+import types
+
+import cbor2
+
+
+# the wasmcloud module, without lattice calls, which need the provider's process
+def _wasmcloud_module():
+    module = types.ModuleType("wasmcloud")
+
+    def log(level, message):
+        print("[{}] {}".format(level.upper(), message), file=sys.stderr, flush=True)
+
+    def call_actor(actor_id, method, arg=None):
+        raise RuntimeError("call_actor isn't available in python worker processes")
+
+    module.log = log
+    module.debug = lambda message: log("debug", message)
+    module.info = lambda message: log("info", message)
+    module.warn = lambda message: log("warn", message)
+    module.error = lambda message: log("error", message)
+    module.call_actor = call_actor
+    return module
+
+
+def _read(stream, size):
+    data = stream.read(size)
+    if data is None or len(data) < size:
+        return None
+    return data
+
+
+# run a request, collecting generator results, since streams can't span requests
+def _handle(request):
+    try:
+        result = broker(request["command"], request["params"])
+        if isinstance(result, dict) and len(result) == 1 and "__stream__" in result:
+            items = []
+            while True:
+                chunk = next_items(result["__stream__"], 1024)
+                items.extend(chunk["items"])
+                if chunk["done"]:
+                    break
+            result = items
+        return {"result": result}
+    except BaseException as e:
+        return {"error": [type(e).__name__, str(e), traceback.format_exc()]}
+
+
+def serve():
+    stdin = sys.stdin.buffer
+    stdout = sys.stdout.buffer
+    # keep print() in python code from corrupting responses
+    sys.stdout = sys.stderr
+    sys.modules["wasmcloud"] = _wasmcloud_module()
+    while True:
+        header = _read(stdin, 4)
+        if header is None:
+            return
+        (size,) = struct.unpack(">I", header)
+        body = _read(stdin, size)
+        if body is None:
+            return
+        response = _handle(cbor2.loads(body))
+        try:
+            data = cbor2.dumps(response)
+        except Exception as e:
+            data = cbor2.dumps({"error": [type(e).__name__, str(e), traceback.format_exc()]})
+        stdout.write(struct.pack(">I", len(data)))
+        stdout.write(data)
+        stdout.flush()
+
+
+serve()
@@ -0,0 +1,370 @@
+//! Python worker subprocesses
+//!
+//! Instead of the in-process engine, the provider can run a pool of python processes,
+//! so CPU-heavy calls run in parallel and a crash in an extension module only takes
+//! down one worker. Each worker runs router.py and worker.py, and handles one request
+//! at a time, exchanging length-prefixed CBOR messages over stdin and stdout.
+//!
+//! Requests are taken from a shared queue by whichever worker is idle. Commands that
+//! load or reload modules are sent to every worker, and the latest load of each module
+//! that is still in use is repeated when a worker is restarted. A worker that exits,
+//! or doesn't respond within the task's timeout, is killed and restarted.
+
+use lazy_static::lazy_static;
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+use serde_value::Value;
+use std::{
+    collections::BTreeMap,
+    process::Stdio,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, BufReader},
+    process::{Child, ChildStdin, ChildStdout, Command},
+    sync::{mpsc, oneshot, Mutex, RwLock},
+};
+
+use crate::pime::{Error, ErrorKind, PyTask};
+
+/// python source run by each worker, after the router
+const WORKER_SOURCE: &str = include_str!("worker.py");
+/// commands that every worker runs
+const BROADCAST_COMMANDS: &[&str] = &["__load__", "__reload__"];
+/// command whose requests are repeated when a worker restarts
+const LOAD_COMMAND: &str = "__load__";
+/// how long to wait before restarting a worker
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+/// number of requests that can wait for a worker
+const QUEUE_SIZE: usize = 1024;
+
+lazy_static! {
+    static ref POOL: RwLock<Option<Arc<Pool>>> = RwLock::new(None);
+    /// params of the load requests sent to every worker, by python_main path
+    static ref LOADS: std::sync::Mutex<BTreeMap<String, BTreeMap<String, Value>>> =
+        std::sync::Mutex::new(BTreeMap::new());
+}
+
+/// how to start a worker process
+#[derive(Clone, Debug)]
+pub(crate) struct WorkerCommand {
+    /// path of the python interpreter
+    pub(crate) python: String,
+    /// python source of the router
+    pub(crate) router: &'static str,
+}
+
+type Reply = oneshot::Sender<Result<Option<Value>, Error>>;
+
+/// a request for a worker
+#[derive(Debug)]
+struct Job {
+    command: Value,
+    params: BTreeMap<String, Value>,
+    timeout: Option<Duration>,
+    reply: Reply,
+}
+
+struct Pool {
+    /// requests for any worker
+    queue: mpsc::Sender<Job>,
+    /// requests for each worker
+    workers: Vec<mpsc::Sender<Job>>,
+    /// requests that haven't returned
+    pending: Arc<AtomicUsize>,
+}
+
+/// Start a pool of workers
+pub(crate) async fn start(command: WorkerCommand, count: u8) {
+    let (queue, rx) = mpsc::channel(QUEUE_SIZE);
+    let shared = Arc::new(Mutex::new(rx));
+    LOADS.lock().unwrap().clear();
+    let mut workers = Vec::new();
+    for index in 0..count.max(1) {
+        let (tx, own) = mpsc::channel(QUEUE_SIZE);
+        workers.push(tx);
+        tokio::spawn(run_worker(index, command.clone(), shared.clone(), own));
+    }
+    info!("started {} python workers", workers.len());
+    *POOL.write().await = Some(Arc::new(Pool {
+        queue,
+        workers,
+        pending: Arc::new(AtomicUsize::new(0)),
+    }));
+}
+
+/// Stop the workers. Requests in progress are finished first.
+pub(crate) async fn stop() {
+    POOL.write().await.take();
+}
+
+pub(crate) async fn is_started() -> bool {
+    POOL.read().await.is_some()
+}
+
+/// Returns the number of requests that haven't returned
+pub(crate) async fn pending_tasks() -> usize {
+    match POOL.read().await.as_ref() {
+        Some(pool) => pool.pending.load(Ordering::SeqCst),
+        None => 0,
+    }
+}
+
+/// Returns the number of requests waiting for a worker
+pub(crate) async fn queued_tasks() -> usize {
+    match POOL.read().await.as_ref() {
+        Some(pool) => QUEUE_SIZE.saturating_sub(pool.queue.capacity()),
+        None => 0,
+    }
+}
+
+/// Stop repeating the load of a python_main module when workers restart,
+/// because no service uses it
+pub(crate) fn forget(main_path: &str) {
+    LOADS.lock().unwrap().remove(main_path);
+}
+
+/// Run a task in a worker, or in every worker for commands that load modules
+pub(crate) async fn call(task: PyTask) -> Result<Option<Value>, Error> {
+    let pool = match POOL.read().await.as_ref() {
+        Some(pool) => pool.clone(),
+        None => {
+            return Err(Error::new(
+                ErrorKind::PySyncEngineStateError,
+                "python workers are not running",
+            ))
+        }
+    };
+    let _pending = Pending::new(pool.pending.clone());
+    pool.call(task).await
+}
+
+/// Counts a request as pending until it is dropped, which is also when the caller
+/// stops waiting for it
+struct Pending(Arc<AtomicUsize>);
+
+impl Pending {
+    fn new(count: Arc<AtomicUsize>) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        Pending(count)
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Pool {
+    async fn call(&self, task: PyTask) -> Result<Option<Value>, Error> {
+        let broadcast =
+            matches!(task.command(), Value::String(c) if BROADCAST_COMMANDS.contains(&c.as_str()));
+        if !broadcast {
+            let (job, rx) = job(&task);
+            self.queue.send(job).await.map_err(Error::from)?;
+            return rx.await.unwrap_or_else(|_| Err(worker_stopped()));
+        }
+        if matches!(task.command(), Value::String(c) if c == LOAD_COMMAND) {
+            if let Some(Value::String(path)) = task.params().get("path") {
+                LOADS
+                    .lock()
+                    .unwrap()
+                    .insert(path.clone(), task.params().clone());
+            }
+        }
+        let mut replies = Vec::new();
+        for worker in self.workers.iter() {
+            let (job, rx) = job(&task);
+            worker.send(job).await.map_err(Error::from)?;
+            replies.push(rx);
+        }
+        let mut result = Ok(None);
+        for rx in replies {
+            let reply = rx.await.unwrap_or_else(|_| Err(worker_stopped()));
+            // report the first error
+            if result.is_ok() {
+                result = reply;
+            }
+        }
+        result
+    }
+}
+
+fn job(task: &PyTask) -> (Job, oneshot::Receiver<Result<Option<Value>, Error>>) {
+    let (reply, rx) = oneshot::channel();
+    let job = Job {
+        command: task.command().clone(),
+        params: task.params().clone(),
+        timeout: task.timeout(),
+        reply,
+    };
+    (job, rx)
+}
+
+fn worker_stopped() -> Error {
+    Error::new(ErrorKind::ExecError, "python worker stopped")
+}
+
+/// Run requests in a worker process, restarting it when it fails, until the pool stops
+async fn run_worker(
+    index: u8,
+    command: WorkerCommand,
+    shared: Arc<Mutex<mpsc::Receiver<Job>>>,
+    mut own: mpsc::Receiver<Job>,
+) {
+    loop {
+        let mut process = match Process::spawn(&command) {
+            Ok(process) => process,
+            Err(e) => {
+                error!("starting python worker {}: {}", index, e);
+                tokio::time::sleep(RESTART_DELAY).await;
+                continue;
+            }
+        };
+        debug!("python worker {} started", index);
+        // load the modules that other workers have
+        let previous = LOADS.lock().unwrap().clone();
+        let load = Value::String(LOAD_COMMAND.to_string());
+        for params in previous.values() {
+            match process.request(&load, params, None).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => error!("python worker {} failed to load module: {}", index, e),
+                Err(e) => error!("python worker {} failed: {}", index, e),
+            }
+        }
+        loop {
+            // this worker's own requests come first
+            let job = tokio::select! {
+                biased;
+                job = own.recv() => job,
+                job = async { shared.lock().await.recv().await } => job,
+            };
+            let job = match job {
+                Some(job) => job,
+                None => {
+                    process.kill().await;
+                    return;
+                }
+            };
+            match process
+                .request(&job.command, &job.params, job.timeout)
+                .await
+            {
+                Ok(result) => {
+                    let _ = job.reply.send(result);
+                }
+                Err(e) => {
+                    error!("python worker {} failed, restarting: {}", index, e);
+                    let _ = job.reply.send(Err(e));
+                    process.kill().await;
+                    break;
+                }
+            }
+        }
+        tokio::time::sleep(RESTART_DELAY).await;
+    }
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    command: &'a Value,
+    params: &'a BTreeMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    result: Option<Value>,
+    /// exception, message, and traceback
+    error: Option<(String, String, String)>,
+}
+
+/// a running worker process
+struct Process {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Process {
+    fn spawn(command: &WorkerCommand) -> std::io::Result<Process> {
+        let mut child = Command::new(&command.python)
+            .arg("-u")
+            .arg("-c")
+            .arg(format!("{}\n{}", command.router, WORKER_SOURCE))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child.stdin.take().expect("piped stdin");
+        let stdout = BufReader::new(child.stdout.take().expect("piped stdout"));
+        Ok(Process {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    /// Send a request and wait for the response. The outer error means the process
+    /// failed or timed out, and must be restarted. The inner result is from python.
+    async fn request(
+        &mut self,
+        command: &Value,
+        params: &BTreeMap<String, Value>,
+        timeout: Option<Duration>,
+    ) -> Result<Result<Option<Value>, Error>, Error> {
+        let body = minicbor_ser::to_vec(&Request { command, params })
+            .map_err(|e| Error::new(ErrorKind::PackError, e))?;
+        let exchange = self.exchange(&body);
+        let response = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, exchange).await {
+                Ok(response) => response,
+                Err(_) => {
+                    return Err(Error::new(
+                        ErrorKind::Timeout,
+                        format!("no result after {:?}", timeout),
+                    ))
+                }
+            },
+            None => exchange.await,
+        }
+        .map_err(|e| Error::new(ErrorKind::ExecError, format!("python worker: {}", e)))?;
+        let response: Response = match minicbor_ser::from_slice(&response) {
+            Ok(response) => response,
+            Err(e) => return Ok(Err(Error::new(ErrorKind::UnpackError, e))),
+        };
+        Ok(match response.error {
+            Some((exception, message, traceback)) => Err(Error {
+                kind: ErrorKind::PyException,
+                message,
+                exception: Some(exception),
+                traceback: Some(traceback),
+            }),
+            None => Ok(response.result),
+        })
+    }
+
+    async fn exchange(&mut self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        self.stdin
+            .write_all(&(body.len() as u32).to_be_bytes())
+            .await?;
+        self.stdin.write_all(body).await?;
+        self.stdin.flush().await?;
+        let mut header = [0u8; 4];
+        self.stdout.read_exact(&mut header).await?;
+        let mut response = vec![0u8; u32::from_be_bytes(header) as usize];
+        self.stdout.read_exact(&mut response).await?;
+        Ok(response)
+    }
+
+    async fn kill(&mut self) {
+        if let Err(e) = self.child.kill().await {
+            debug!("killing python worker: {}", e);
+        }
+    }
+}
@@ -9,5 +9,7 @@ def main(command, arg):
     if command == "reload":
         reload(dispatch)
         return True
+    elif command == "caller":
+        return [arg["actor"], arg["link_name"]]
     else:
         return dispatch.handle(command, arg["arg"])
@@ -1,7 +1,9 @@
 use log::debug;
 use minicbor_ser::{from_slice, to_vec};
-use pyprov::Service;
+use pyprov::{CallContext, Service};
 use serde_value::Value;
+use std::collections::HashMap;
+use wasmbus_rpc::error::RpcError;
 
 /// Test service.invoke. This is an internal test - not how an actor would invoke the service.
 #[tokio::test]
@@ -19,5 +21,78 @@ async fn call_service() {
     let res: String = from_slice(&service.invoke("h.hello", &buf).await.unwrap()).unwrap();
     assert_eq!(res.as_str(), "Hello Sam!");
 
+    // generator results are collected into a list when there's no actor to stream to
+    let buf = to_vec(&Value::I32(40)).unwrap();
+    let res: Vec<i32> = from_slice(&service.invoke("c.count", &buf).await.unwrap()).unwrap();
+    assert_eq!(res, (0..40).collect::<Vec<i32>>());
+
+    let health = service.health().await;
+    assert!(health.healthy, "{:?}", health.message);
+    assert!(health.message.unwrap().starts_with("engine=started"));
+
+    let caller = CallContext {
+        actor: Some("MACTOR".to_string()),
+        link_name: Some("default".to_string()),
+        ..Default::default()
+    };
+    let res: Vec<String> =
+        from_slice(&service.invoke_from(&caller, "caller", &[]).await.unwrap()).unwrap();
+    assert_eq!(res, ["MACTOR", "default"]);
+
+    // a method that takes too long times out, without holding up other calls
+    let mut values = HashMap::new();
+    values.insert(
+        "config_json".to_string(),
+        r#"{"method_timeouts_ms": {"s.sleep": 100, "w.stalled": 100}}"#.to_string(),
+    );
+    let limited = Service::try_init(Some(values))
+        .await
+        .expect("init with timeout");
+    let buf = to_vec(&Value::F64(2.0)).unwrap();
+    let res = limited.invoke("s.sleep", &buf).await;
+    assert!(matches!(res, Err(RpcError::Timeout(_))), "sleep timed out");
+    let buf = to_vec(&Value::I32(5)).unwrap();
+    let res: i32 = from_slice(&limited.invoke("f.factorial", &buf).await.unwrap()).unwrap();
+    assert_eq!(res, 120, "5!");
+
+    // generators that time out or raise are closed, so the router doesn't keep them
+    let buf = to_vec(&Value::I32(3)).unwrap();
+    let res = limited.invoke("w.stalled", &buf).await;
+    assert!(
+        matches!(res, Err(RpcError::Timeout(_))),
+        "stalled timed out"
+    );
+    let res = limited.invoke("w.broken", &buf).await;
+    assert!(matches!(res, Err(RpcError::Other(_))), "broken raised");
+    let health = limited.health().await;
+    let message = health.message.unwrap_or_default();
+    assert!(message.contains("python_streams=0"), "{}", message);
+
+    // parameters are checked against the schema
+    let mut values = HashMap::new();
+    values.insert(
+        "config_json".to_string(),
+        r#"{"schema_path": "tests/schema.json"}"#.to_string(),
+    );
+    let checked = Service::try_init(Some(values))
+        .await
+        .expect("init with schema");
+    let buf = to_vec(&Value::I32(6)).unwrap();
+    let res: u64 = from_slice(&checked.invoke("f.factorial", &buf).await.unwrap()).unwrap();
+    assert_eq!(res, 720, "6!");
+    let buf = to_vec("six").unwrap();
+    match checked.invoke("f.factorial", &buf).await {
+        Err(RpcError::Deser(e)) => assert!(e.contains("$: expected integer"), "{}", e),
+        res => panic!("expected schema error, got {:?}", res),
+    }
+
+    // the engine's settings can't be changed by a later service
+    let mut values = HashMap::new();
+    values.insert("config_json".to_string(), r#"{"workers": 2}"#.to_string());
+    match Service::try_init(Some(values)).await {
+        Err(RpcError::ProviderInit(e)) => assert!(e.starts_with("workers can't"), "{}", e),
+        res => panic!("expected init error, got {:?}", res.map(|_| ())),
+    }
+
     Service::shutdown().await;
 }
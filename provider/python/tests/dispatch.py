@@ -1,3 +1,6 @@
+import time
+
+
 # compute n factorial
 def factorial(n):
     val = 1
@@ -11,6 +14,32 @@ def big_response(n):
     return val
 
 
+# wait, then return the number of seconds waited
+def sleep(seconds):
+    time.sleep(seconds)
+    return seconds
+
+
+# generate the numbers from 0 to n-1
+def count(n):
+    for i in range(n):
+        yield i
+
+
+# generate the numbers from 0 to n-1, slowly
+def stalled(n):
+    for i in range(n):
+        time.sleep(0.5)
+        yield i
+
+
+# generate n numbers, then fail
+def broken(n):
+    for i in range(n):
+        yield i
+    raise ValueError("broken after {} items".format(n))
+
+
 # say hello
 def say_hello(name):
     return "Hello {}!".format(name)
@@ -24,6 +53,18 @@ def handle(command, arg):
     if command.endswith("hello"):
         return say_hello(arg)
 
+    if command.endswith("stalled"):
+        return stalled(arg)
+
+    if command.endswith("broken"):
+        return broken(arg)
+
+    if command.endswith("count"):
+        return count(arg)
+
+    if command.endswith("sleep"):
+        return sleep(arg)
+
     if command.endswith("big_response"):
         return big_response(arg)
 
@@ -0,0 +1,100 @@
+use minicbor_ser::{from_slice, to_vec};
+use pyprov::Service;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// how long to wait for a change to be reloaded
+const RELOAD_WAIT: Duration = Duration::from_secs(10);
+
+const MAIN_SOURCE: &str = r#"
+import os
+import sys
+
+# the dispatch program is outside the package and the sys_path folders
+sys.path.insert(0, os.path.realpath(os.path.join(os.path.dirname(__file__), "..", "lib")))
+import reload_dispatch
+from . import version
+
+
+def main(command, arg):
+    if command == "version":
+        return version.VERSION
+    return reload_dispatch.handle(command, arg["arg"])
+"#;
+
+/// python_main package and python_dispatch file in a temporary folder
+fn write_module(dir: &Path) -> (PathBuf, PathBuf) {
+    let main = dir.join("reload_main");
+    let lib = dir.join("lib");
+    std::fs::create_dir_all(&main).unwrap();
+    std::fs::create_dir_all(&lib).unwrap();
+    std::fs::write(main.join("__init__.py"), MAIN_SOURCE).unwrap();
+    std::fs::write(main.join("version.py"), "VERSION = '1'\n").unwrap();
+    let dispatch = lib.join("reload_dispatch.py");
+    std::fs::write(&dispatch, "def handle(command, arg):\n    return 'hello'\n").unwrap();
+    (main, dispatch)
+}
+
+async fn call(service: &Service, command: &str) -> String {
+    let buf = to_vec("").unwrap();
+    from_slice(&service.invoke(command, &buf).await.unwrap()).unwrap()
+}
+
+/// Call until the result is `expected`, or the wait is over
+async fn wait_for(service: &Service, command: &str, expected: &str) -> Result<(), String> {
+    let start = Instant::now();
+    loop {
+        let value = call(service, command).await;
+        if value == expected {
+            return Ok(());
+        }
+        if start.elapsed() > RELOAD_WAIT {
+            return Err(format!(
+                "{} returned {:?}, expected {:?}",
+                command, value, expected
+            ));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Edited module files are reloaded, and the next call runs the new code
+#[tokio::test]
+async fn reload_module() {
+    let dir = std::env::temp_dir().join(format!("pyprov-reload-{}", rand::random::<u32>()));
+    let (main, dispatch) = write_module(&dir);
+    let mut values = HashMap::new();
+    values.insert(
+        "config_json".to_string(),
+        serde_json::json!({
+            "python_main": main.to_string_lossy(),
+            "python_dispatch": dispatch.to_string_lossy(),
+        })
+        .to_string(),
+    );
+    let service = Service::try_init(Some(values)).await.expect("init");
+
+    assert_eq!(call(&service, "greet").await, "hello");
+    assert_eq!(call(&service, "version").await, "1");
+
+    // a file in the python_main package
+    std::fs::write(main.join("version.py"), "VERSION = '2'\n").unwrap();
+    let main_reloaded = wait_for(&service, "version", "2").await;
+
+    // the python_dispatch file, outside the package
+    std::fs::write(
+        &dispatch,
+        "def handle(command, arg):\n    return 'goodbye'\n",
+    )
+    .unwrap();
+    let dispatch_reloaded = wait_for(&service, "greet", "goodbye").await;
+
+    // stop the engine before checking, so a failure doesn't wait for it
+    Service::shutdown().await;
+    let _ = std::fs::remove_dir_all(&dir);
+    main_reloaded.expect("python_main reloaded");
+    dispatch_reloaded.expect("python_dispatch reloaded");
+}
@@ -0,0 +1,41 @@
+use pyprov::Service;
+use std::collections::HashMap;
+use wasmbus_rpc::error::RpcError;
+
+fn link_values(config: serde_json::Value) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    values.insert("config_json".to_string(), config.to_string());
+    values
+}
+
+/// Services initialized at once start the engine once, and a later service
+/// can't change the engine's settings
+#[tokio::test]
+async fn start_engine() {
+    let addr = format!("127.0.0.1:{}", rand::random::<u16>() % 20000 + 40000);
+    let config = serde_json::json!({
+        "python_main": "tests",
+        "metrics_addr": addr,
+    });
+    let (first, second) = tokio::join!(
+        Service::try_init(Some(link_values(config.clone()))),
+        Service::try_init(Some(link_values(config))),
+    );
+    let first = first.map(|_| ());
+    let second = second.map(|_| ());
+
+    let other_addr = Service::try_init(Some(link_values(serde_json::json!({
+        "python_main": "tests",
+        "metrics_addr": "127.0.0.1:1",
+    }))))
+    .await
+    .map(|_| ());
+
+    Service::shutdown().await;
+    first.expect("first service");
+    second.expect("second service");
+    match other_addr {
+        Err(RpcError::ProviderInit(e)) => assert!(e.starts_with("metrics_addr can't"), "{}", e),
+        res => panic!("expected init error, got {:?}", res),
+    }
+}
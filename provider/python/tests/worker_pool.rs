@@ -0,0 +1,79 @@
+use minicbor_ser::{from_slice, to_vec};
+use pyprov::Service;
+use serde_value::Value;
+use std::{collections::HashMap, time::Duration};
+use wasmbus_rpc::error::RpcError;
+
+fn link_values(config: serde_json::Value) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    values.insert("config_json".to_string(), config.to_string());
+    values
+}
+
+/// Health message of the engine, after a health check in the worker
+async fn health(service: &Service) -> String {
+    let health = service.health().await;
+    assert!(health.healthy, "{:?}", health.message);
+    health.message.unwrap_or_default()
+}
+
+/// Test calls in a python worker process, and its restart after a timeout
+#[tokio::test]
+async fn worker_pool() {
+    // one worker, so every call after a restart goes to the restarted worker
+    let service = Service::try_init(Some(link_values(serde_json::json!({
+        "python_main": "tests",
+        "workers": 1,
+        "method_timeouts_ms": {"s.sleep": 200},
+    }))))
+    .await
+    .expect("init with workers");
+
+    let buf = to_vec(&Value::I32(10)).unwrap();
+    let res: i32 = from_slice(&service.invoke("f.factorial", &buf).await.unwrap()).unwrap();
+    assert_eq!(res, 3628800, "10!");
+
+    // generator results are collected in the worker
+    let buf = to_vec(&Value::I32(5)).unwrap();
+    let res: Vec<i32> = from_slice(&service.invoke("c.count", &buf).await.unwrap()).unwrap();
+    assert_eq!(res, [0, 1, 2, 3, 4]);
+
+    // another module, which is forgotten when its service is dropped
+    let dir = std::env::temp_dir().join(format!("pyprov-worker-{}", rand::random::<u32>()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let extra_path = dir.join("worker_extra.py");
+    std::fs::write(&extra_path, "def main(command, arg):\n    return 'extra'\n").unwrap();
+    let extra = Service::try_init(Some(link_values(serde_json::json!({
+        "python_main": extra_path.to_string_lossy(),
+    }))))
+    .await
+    .expect("init extra module");
+    let res: String = from_slice(&extra.invoke("any", &[]).await.unwrap()).unwrap();
+    assert_eq!(res, "extra");
+    let message = health(&service).await;
+    assert!(message.contains("python_modules=2"), "{}", message);
+    drop(extra);
+
+    // a call that times out kills the worker
+    let buf = to_vec(&Value::F64(5.0)).unwrap();
+    let res = service.invoke("s.sleep", &buf).await;
+    assert!(matches!(res, Err(RpcError::Timeout(_))), "sleep timed out");
+
+    // the restarted worker loads the modules still in use, and runs the next call
+    let buf = to_vec(&Value::I32(5)).unwrap();
+    let res: i32 = from_slice(&service.invoke("f.factorial", &buf).await.unwrap()).unwrap();
+    assert_eq!(res, 120, "5!");
+    let message = health(&service).await;
+    assert!(message.contains("python_modules=1"), "{}", message);
+
+    // a call whose caller stops waiting isn't counted as pending
+    let buf = to_vec(&Value::F64(0.1)).unwrap();
+    let res =
+        tokio::time::timeout(Duration::from_millis(20), service.invoke("s.sleep", &buf)).await;
+    assert!(res.is_err(), "caller stopped waiting");
+    let message = health(&service).await;
+    assert!(message.contains("tasks=0"), "{}", message);
+
+    Service::shutdown().await;
+    let _ = std::fs::remove_dir_all(&dir);
+}